@@ -1,8 +1,49 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/** A running `startWindowCapture` stream */
+export declare class WindowCapture {
+  /** Stop delivering frames */
+  stop(): void
+  /** Whether the stream was stopped */
+  get stopped(): boolean
+}
+
+/**
+ * Apply `actions` to every window that starts matching `match`, as it appears or gets renamed
+ * Windows are watched natively while rules exist; returns the rule id
+ */
+export declare function addWindowRule(rule: WindowRuleOptions): number
+
+/**
+ * Keep an overlay window positioned and stacked just above a target window
+ * The overlay follows the target until `detachOverlay` is called or the target is destroyed
+ */
+export declare function attachOverlay(overlayHandle: unknown, targetHandle: unknown, options?: OverlayOptions | undefined | null): void
+
+/**
+ * Save bounds, state, desktop, opacity and on-top flag of the visible windows matching `query`
+ * Windows without a title, class or path cannot be matched again and are skipped
+ */
+export declare function captureLayout(query?: WindowMatch | undefined | null): Layout
+
+/**
+ * Capture the pixels of a window, or of a region of it, as RGBA
+ * On X11 the part past the screen edges is cut off, so the image can be smaller than asked
+ */
+export declare function captureWindow(handle: unknown, options?: CaptureOptions | undefined | null): RgbaImage
+
 /** Close the window */
 export declare function closeWindow(handle: unknown): void
 
+/** Stop an overlay from following its target, returns false if it was not attached */
+export declare function detachOverlay(overlayHandle: unknown): boolean
+
+/**
+ * Animate window opacity to `target` over `durationMs` in a native thread (default easing: linear)
+ * Resolves to `true` when done, or `false` if a newer fade on the same window cancelled it
+ */
+export declare function fadeWindowOpacity(handle: unknown, target: number, durationMs: number, easing?: Easing | undefined | null): Promise<boolean>
+
 /** Find the first window matching the title */
 export declare function findWindowByTitle(title: string, exact?: boolean | undefined | null): WindowInfo | null
 
@@ -19,42 +60,377 @@ export declare function focusWindow(handle: unknown): void
 /** Get the handle of the currently active (foreground) window */
 export declare function getActiveWindow(): number | null
 
+/** Get the index of the currently shown virtual desktop */
+export declare function getCurrentDesktop(): number | null
+
+/** Get all virtual desktops (workspaces) */
+export declare function getDesktops(): Array<DesktopInfo>
+
+/** Get all connected monitors with their bounds, work areas and scale factors */
+export declare function getMonitors(): Array<MonitorInfo>
+
+/**
+ * Get the window bounds without the cost of a full `getWindowInfo` lookup
+ * If `clientArea` is true, returns the client area instead of the outer frame
+ */
+export declare function getWindowBounds(handle: unknown, clientArea?: boolean | undefined | null): Rect | null
+
+/** Get the window manager flags currently set on a window */
+export declare function getWindowFlags(handle: unknown): WindowFlags
+
+/**
+ * Get the window icon that best fits `preferredSize` pixels (default: the largest one)
+ * The smallest icon at least that large is picked, or the largest available
+ */
+export declare function getWindowIcon(handle: unknown, preferredSize?: number | undefined | null): RgbaImage | null
+
+/** Get every icon size a window provides */
+export declare function getWindowIcons(handle: unknown): Array<RgbaImage>
+
+/** Get window opacity (1.0 when it was never changed) */
+export declare function getWindowOpacity(handle: unknown): number
+
 /** Get window info by handle */
 export declare function getWindowInfo(handle: unknown): WindowInfo | null
 
+/** Get the monitor that contains the largest part of the window */
+export declare function getWindowMonitor(handle: unknown): MonitorInfo | null
+
 /** Get the executable path of the process that owns the window */
 export declare function getWindowProcessPath(handle: unknown): string
 
+/** Get the current window state (minimized, maximized, fullscreen, focused, always on top) */
+export declare function getWindowState(handle: unknown): WindowStateInfo | null
+
+/** Get the window title */
+export declare function getWindowTitle(handle: unknown): string
+
+/** Get the pointer position, the monitor under it and the held buttons and modifiers */
+export declare function getCursorPosition(): CursorPosition
+
 /** Get all visible windows */
 export declare function getWindows(): Array<WindowInfo>
 
+/** Get the topmost window at a point in screen coordinates, ignoring `skipHandles` */
+export declare function getWindowAtPoint(x: number, y: number, options?: WindowAtPointOptions | undefined | null): WindowInfo | null
+
+/** Get all visible windows ordered from bottom to top (the last one is the topmost window) */
+export declare function getWindowsInStackingOrder(): Array<WindowInfo>
+
 /** Check if click-through is currently enabled for a window */
 export declare function isClickThrough(handle: unknown): boolean
 
+/**
+ * Keep a window click-through, making it interactive while `modifiers` (e.g. `Ctrl+Alt`) are held
+ * `callback` receives an `InteractEvent` on every transition
+ */
+export declare function enableInteractOnModifier(handle: unknown, modifiers: string, callback?: ((event: InteractEvent) => void) | undefined | null): void
+
+/**
+ * Stop watching the modifiers of a window, which is left click-through
+ * Returns `false` if interact mode was not enabled
+ */
+export declare function disableInteractOnModifier(handle: unknown): boolean
+
 /** Kill the process associated with the window */
 export declare function killWindowProcess(handle: unknown): void
 
+/** List the registered window rules, in the order they are evaluated */
+export declare function listWindowRules(): Array<WindowRule>
+
+/** Lower a window to the bottom of the stacking order */
+export declare function lowerWindow(handle: unknown): void
+
+/** Move a window to another virtual desktop */
+export declare function moveWindowToDesktop(handle: unknown, index: number): void
+
+/** Restack a window directly above a sibling window */
+export declare function placeWindowAbove(handle: unknown, sibling: unknown): void
+
+/** Raise a window to the top of the stacking order */
+export declare function raiseWindow(handle: unknown): void
+
+/**
+ * Register a global hotkey such as `Ctrl+Shift+F9`, even while another application is focused
+ * `callback` receives the accelerator on every press; fails if another application holds the key
+ */
+export declare function registerHotkey(accelerator: string, callback: (accelerator: string) => void): void
+
+/**
+ * Reapply a layout saved by `captureLayout` to the current windows
+ * Each entry is matched by class and executable (the title breaking ties) to a different window
+ */
+export declare function restoreLayout(layout: Layout): RestoreLayoutResult
+
+/** Remove a window rule, returns false if no rule has this id */
+export declare function removeWindowRule(id: number): boolean
+
+/** Remove any opacity set on the window, restoring its untouched state */
+export declare function removeWindowOpacity(handle: unknown): void
+
 /** Disable click-through on a window (window captures mouse events again) */
 export declare function removeClickThrough(handle: unknown): void
 
+/**
+ * Remove (`false`) or restore (`true`) the title bar and borders of a window
+ * The original decorations are remembered so they can be restored later
+ */
+export declare function setDecorations(handle: unknown, enabled: boolean): void
+
 /** Make a window always on top */
 export declare function setAlwaysOnTop(handle: unknown, onTop: boolean): void
 
 /** Enable click-through on a window (mouse events pass through) */
 export declare function setClickThrough(handle: unknown): void
 
+/**
+ * Make the transparent pixels of an RGBA image click-through
+ * Pixels whose alpha is above `alphaThreshold` (default 0) stay interactive
+ * X11 only, on Windows any mask with opaque pixels is rejected
+ */
+export declare function setInputMaskFromImage(handle: unknown, rgbaBuffer: Buffer, width: number, height: number, alphaThreshold?: number | undefined | null): void
+
+/**
+ * Make only the given rectangles (window-relative) receive mouse input
+ * An empty array makes the window fully click-through, `null` fully interactive
+ * Partial regions need X11, Windows only accepts an empty array or `null`
+ */
+export declare function setInputRegion(handle: unknown, rects?: Array<Rect> | undefined | null): void
+
+/** Add (`true`) or remove (`false`) window manager flags, omitted flags are left untouched */
+export declare function setWindowFlags(handle: unknown, flags: WindowFlags): void
+
+/** Move and resize a window in a single atomic request */
+export declare function setWindowBounds(handle: unknown, bounds: Rect, options?: WindowBoundsOptions | undefined | null): void
+
+/** Set the window icon from one or more RGBA images of different sizes */
+export declare function setWindowIcon(handle: unknown, images: Array<RgbaImage>): void
+
 /** Set window transparency/opacity (0.0 = fully transparent, 1.0 = fully opaque) */
 export declare function setWindowOpacity(handle: unknown, opacity: number): void
 
-/** Set the window state (Minimize, Maximize, Restore) */
+/**
+ * Set the window state (Minimize, Maximize, Restore, Fullscreen, ExitFullscreen, Hide, Show)
+ * Hide unmaps the window without destroying it, Show maps it again
+ */
 export declare function setWindowState(handle: unknown, state: WindowState): void
 
+/** Set the window title */
+export declare function setWindowTitle(handle: unknown, title: string): void
+
+/**
+ * Stream frames of a window to `onFrame`, only when its content changed (XDamage on X11)
+ * Frames are dropped while JS is still busy with the previous one; call `stop()` on the result to end
+ */
+export declare function startWindowCapture(handle: unknown, options: WindowCaptureOptions | undefined | null, onFrame: (frame: RgbaImage) => void): WindowCapture
+
+/** Switch to another virtual desktop */
+export declare function switchDesktop(index: number): void
+
+/**
+ * Set the window type hint (dock, notification, utility, splash, tooltip, desktop, normal...)
+ * Most window managers only honour a type change when the window is next mapped
+ */
+export declare function setWindowType(handle: unknown, windowType: WindowType): void
+
+/**
+ * Unregister a global hotkey
+ * Returns `false` if the hotkey was not registered
+ */
+export declare function unregisterHotkey(accelerator: string): boolean
+
 /**
  * Toggle click-through state on a window
  * Returns the new state: true = click-through enabled, false = disabled
  */
 export declare function toggleClickThrough(handle: unknown): boolean
 
+/** Toggle every flag set to `true`, returns the resulting flags */
+export declare function toggleWindowFlags(handle: unknown, flags: WindowFlags): WindowFlags
+
+/** Options for `captureWindow` */
+export interface CaptureOptions {
+  /** Area to capture, relative to the captured window (default: everything) */
+  region?: Rect
+  /** Capture the window manager frame too instead of the client area only (default: false) */
+  includeFrame?: boolean
+}
+
+/** Pointer position and button/modifier state */
+export interface CursorPosition {
+  /** Position X in virtual screen coordinates */
+  x: number
+  /** Position Y in virtual screen coordinates */
+  y: number
+  /** Monitor under the pointer */
+  monitor?: MonitorInfo
+  /** Whether the left mouse button is held */
+  leftButton: boolean
+  /** Whether the middle mouse button is held */
+  middleButton: boolean
+  /** Whether the right mouse button is held */
+  rightButton: boolean
+  /** Modifier keys held (`Shift`, `Ctrl`, `Alt`, `Super`) */
+  modifiers: Array<string>
+}
+
+/** Represents a virtual desktop (workspace) */
+export interface DesktopInfo {
+  /** Desktop index */
+  index: number
+  /** Desktop name (empty if the window manager does not name desktops) */
+  name: string
+  /** Whether this is the desktop currently shown */
+  current: boolean
+}
+
+/** Emitted when an interact-on-modifier window switches between click-through and interactive */
+export interface InteractEvent {
+  /** Window handle */
+  handle: number
+  /** Whether the window currently receives mouse input */
+  interactive: boolean
+}
+
+/** Saved window placements, plain data that can go through `JSON.stringify` */
+export interface Layout {
+  /** Saved windows, from the bottom to the top of the stacking order */
+  windows: Array<LayoutEntry>
+}
+
+/** A window saved by `captureLayout` */
+export interface LayoutEntry {
+  /** Identifies the window on restore; the title only breaks ties when the class or path is known */
+  match: WindowMatch
+  /** Outer frame bounds */
+  bounds: Rect
+  /** Whether the window was minimized */
+  minimized: boolean
+  /** Whether the window was maximized */
+  maximized: boolean
+  /** Whether the window was fullscreen */
+  fullscreen: boolean
+  /** Virtual desktop (-1 when shown on all desktops or unknown) */
+  desktop: number
+  /** Window opacity (0.0 to 1.0) */
+  opacity: number
+  /** Whether the window was kept above other windows */
+  alwaysOnTop: boolean
+}
+
+/** Represents information about a monitor (display output) */
+export interface MonitorInfo {
+  /** Output name (e.g. "HDMI-1" on X11, "\\.\DISPLAY1" on Windows) */
+  name: string
+  /** Full monitor bounds in virtual screen coordinates */
+  bounds: Rect
+  /** Usable area, excluding panels, docks and taskbars */
+  workArea: Rect
+  /** Whether this is the primary monitor */
+  primary: boolean
+  /** Refresh rate in Hz (0 when unknown) */
+  refreshRate: number
+  /** Scale factor relative to 96 DPI (1.0 = 100%) */
+  scaleFactor: number
+}
+
+/** Options for `attachOverlay` */
+export interface OverlayOptions {
+  /** Point of the target's client area the overlay is aligned to (default: TopLeft) */
+  anchor?: OverlayAnchor
+  /** Offset added to the anchored position (default: none) */
+  offset?: Point
+  /** Resize the overlay to the target's client area (default: false) */
+  matchSize?: boolean
+  /** Hide the overlay while the target is minimized (default: true) */
+  hideWhenTargetMinimized?: boolean
+  /** Hide the overlay while neither the target nor the overlay has the focus (default: false) */
+  hideWhenTargetUnfocused?: boolean
+}
+
+/** A point in screen coordinates */
+export interface Point {
+  x: number
+  y: number
+}
+
+/** Represents a rectangle in screen coordinates */
+export interface Rect {
+  /** Position X */
+  x: number
+  /** Position Y */
+  y: number
+  /** Width */
+  width: number
+  /** Height */
+  height: number
+}
+
+/** Outcome of `restoreLayout` */
+export interface RestoreLayoutResult {
+  /** Handles of the windows that were restored */
+  restored: Array<number>
+  /** Entries no current window matched */
+  unmatched: Array<LayoutEntry>
+  /** Windows that matched but could not be fully restored, with the reason */
+  errors: Array<string>
+}
+
+/** An RGBA image, such as a window capture or icon */
+export interface RgbaImage {
+  /** Image width in pixels */
+  width: number
+  /** Image height in pixels */
+  height: number
+  /** Pixels as tightly packed RGBA, row by row from the top */
+  data: Buffer
+}
+
+/** Options for `getWindowAtPoint` */
+export interface WindowAtPointOptions {
+  /** Windows to look through, such as your own overlays */
+  skipHandles?: Array<number>
+}
+
+/** Options for `startWindowCapture` */
+export interface WindowCaptureOptions {
+  /** Maximum frames per second delivered (default: 30) */
+  maxFps?: number
+  /** Area to capture, relative to the captured window (default: everything) */
+  region?: Rect
+  /** Capture the window manager frame too instead of the client area only (default: false) */
+  includeFrame?: boolean
+}
+
+/** Options for moving and resizing a window */
+export interface WindowBoundsOptions {
+  /** Interpret the bounds as the client area instead of the outer frame (default: false) */
+  clientArea?: boolean
+  /** Allow the window manager to animate the change (Windows only, default: false) */
+  animate?: boolean
+}
+
+/**
+ * Window manager flags (EWMH `_NET_WM_STATE` on X11)
+ * When setting, `true` adds a flag, `false` removes it and omitted flags are left untouched
+ */
+export interface WindowFlags {
+  /** Show the window on all virtual desktops (X11 only) */
+  sticky?: boolean
+  /** Hide the window from the taskbar */
+  skipTaskbar?: boolean
+  /** Hide the window from pagers and workspace switchers (X11 only) */
+  skipPager?: boolean
+  /** Cover the whole monitor without decorations (X11 only) */
+  fullscreen?: boolean
+  /** Keep the window below other windows */
+  below?: boolean
+  /** Keep the window above other windows */
+  above?: boolean
+  /** Ask for the user's attention (flashes the taskbar entry) */
+  demandsAttention?: boolean
+}
+
 /** Represents information about a window */
 export interface WindowInfo {
   /** Window handle (HWND on Windows, Window ID on X11, NSWindow pointer on macOS) */
@@ -63,7 +439,7 @@ export interface WindowInfo {
   title: string
   /** Process ID that owns the window */
   processId: number
-  /** Window class name (Windows only, empty on other platforms) */
+  /** Window class name (WM_CLASS class on X11, empty on macOS) */
   className: string
   /** Whether the window is visible */
   visible: boolean
@@ -79,10 +455,141 @@ export interface WindowInfo {
   height: number
   /** Path to the executable process that owns the window */
   path: string
+  /** Position in the stacking order, counted from the bottom (-1 when unknown) */
+  zIndex: number
+  /** Virtual desktop the window is on (-1 when shown on all desktops or unknown) */
+  desktop: number
+  /** Whether the window is minimized (iconic) */
+  minimized: boolean
+  /** Whether the window is maximized */
+  maximized: boolean
+  /** Whether the window is fullscreen */
+  fullscreen: boolean
+  /** Whether the window has the input focus */
+  focused: boolean
+  /** Whether the window is kept above other windows */
+  alwaysOnTop: boolean
+  /** Window type hint (EWMH `_NET_WM_WINDOW_TYPE` on X11) */
+  windowType: WindowType
+}
+
+/** Criteria a window must meet, every field that is set must match */
+export interface WindowMatch {
+  /** Part of the title (case-insensitive) */
+  title?: string
+  /** Window class name (case-insensitive) */
+  className?: string
+  /** Executable path, or only its file name (case-insensitive) */
+  path?: string
+  /** Process ID that owns the window */
+  processId?: number
+  /** Window type hint */
+  windowType?: WindowType
+}
+
+/** A rule registered with `addWindowRule` */
+export interface WindowRule {
+  /** Rule id, used by `removeWindowRule` */
+  id: number
+  /** Windows the rule applies to */
+  match: WindowMatch
+  /** Changes applied to each window when it starts matching */
+  actions: WindowRuleActions
+  /** Whether the rule is removed after it applied to a first window */
+  once: boolean
+}
+
+/** Changes applied by a window rule, every field that is set is applied */
+export interface WindowRuleActions {
+  /** Window opacity (0.0 to 1.0) */
+  opacity?: number
+  /** Keep the window above other windows */
+  alwaysOnTop?: boolean
+  /** Window manager flags to add (`true`) or remove (`false`), such as `sticky` */
+  flags?: WindowFlags
+  /** Make the window click-through */
+  clickThrough?: boolean
+  /** Show or strip the title bar and borders */
+  decorations?: boolean
+  /** Move the window to this monitor (index in `getMonitors()`), keeping its offset in the work area */
+  monitor?: number
+  /** Move and resize the window (outer frame), takes precedence over `monitor` */
+  bounds?: Rect
+  /** Move the window to this virtual desktop */
+  desktop?: number
+  /** Window state to apply */
+  state?: WindowState
+}
+
+/** Options for `addWindowRule` */
+export interface WindowRuleOptions {
+  /** Windows the rule applies to */
+  match: WindowMatch
+  /** Changes applied to each window when it starts matching */
+  actions: WindowRuleActions
+  /** Remove the rule after it applied to a first window (default: false) */
+  once?: boolean
+}
+
+/** Represents the current state of a window */
+export interface WindowStateInfo {
+  /** Whether the window is minimized (iconic) */
+  minimized: boolean
+  /** Whether the window is maximized */
+  maximized: boolean
+  /** Whether the window is fullscreen */
+  fullscreen: boolean
+  /** Whether the window has the input focus */
+  focused: boolean
+  /** Whether the window is kept above other windows */
+  alwaysOnTop: boolean
+}
+
+/** Easing curves for `fadeWindowOpacity` */
+export declare const enum Easing {
+  Linear = 0,
+  EaseIn = 1,
+  EaseOut = 2,
+  EaseInOut = 3
+}
+
+/** Where an attached overlay sits relative to its target */
+export declare const enum OverlayAnchor {
+  TopLeft = 0,
+  Top = 1,
+  TopRight = 2,
+  Left = 3,
+  Center = 4,
+  Right = 5,
+  BottomLeft = 6,
+  Bottom = 7,
+  BottomRight = 8
 }
 
 export declare const enum WindowState {
   Minimize = 0,
   Maximize = 1,
-  Restore = 2
+  Restore = 2,
+  Fullscreen = 3,
+  ExitFullscreen = 4,
+  Hide = 5,
+  Show = 6
+}
+
+/** Window type hints telling the window manager how to decorate, focus and stack a window */
+export declare const enum WindowType {
+  Normal = 0,
+  Desktop = 1,
+  Dock = 2,
+  Toolbar = 3,
+  Menu = 4,
+  Utility = 5,
+  Splash = 6,
+  Dialog = 7,
+  DropdownMenu = 8,
+  PopupMenu = 9,
+  Tooltip = 10,
+  Notification = 11,
+  Combo = 12,
+  Dnd = 13
 }
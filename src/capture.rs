@@ -0,0 +1,73 @@
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use crate::{platform, Rect, RgbaImage};
+
+/// Frames queued for JS at most; further frames are dropped until JS catches up
+pub type FrameCallback = ThreadsafeFunction<RgbaImage, (), RgbaImage, Status, false, false, 1>;
+
+/// A running `startWindowCapture` stream
+#[napi]
+pub struct WindowCapture {
+  stop: Arc<AtomicBool>,
+}
+
+#[napi]
+impl WindowCapture {
+  /// Stop delivering frames
+  #[napi]
+  pub fn stop(&self) {
+    self.stop.store(true, Ordering::Relaxed);
+  }
+
+  /// Whether the stream was stopped
+  #[napi(getter)]
+  pub fn stopped(&self) -> bool {
+    self.stop.load(Ordering::Relaxed)
+  }
+}
+
+/// Stream frames of a window to `callback` until stopped or the window is destroyed
+pub fn start(
+  handle: i64,
+  region: Option<Rect>,
+  include_frame: bool,
+  max_fps: f64,
+  callback: FrameCallback,
+) -> Result<WindowCapture> {
+  if max_fps.is_nan() || max_fps <= 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "maxFps must be greater than 0",
+    ));
+  }
+  let min_interval = Duration::from_secs_f64(1.0 / max_fps);
+
+  let stop = Arc::new(AtomicBool::new(false));
+  let (ready, started) = mpsc::channel();
+
+  let thread_stop = stop.clone();
+  thread::spawn(move || {
+    platform::watch_window_frames(
+      handle,
+      region,
+      include_frame,
+      min_interval,
+      &thread_stop,
+      ready,
+      |frame| callback.call(frame, ThreadsafeFunctionCallMode::NonBlocking) == Status::Ok,
+    );
+    // Also flag streams ended by the window going away
+    thread_stop.store(true, Ordering::Relaxed);
+  });
+
+  started
+    .recv()
+    .map_err(|_| Error::new(Status::GenericFailure, "Capture thread exited"))??;
+  Ok(WindowCapture { stop })
+}
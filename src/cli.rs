@@ -0,0 +1,466 @@
+//! `clicktt` command-line interface, sharing the platform code with the Node addon
+
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+
+use crate::platform::{self, WindowState};
+use crate::rules::WindowEvent;
+use crate::{WindowInfo, WindowStateInfo};
+
+/// The command ran but the platform reported an error, or nothing matched
+const EXIT_FAILURE: i32 = 1;
+/// The command line could not be understood
+const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "\
+Usage: clicktt [--json] <command> [args]
+
+Commands:
+  list [--all]                          List visible windows (--all includes hidden ones)
+  find <title> [--exact]                List windows whose title contains <title>
+  click-through <on|off|toggle> <handle>
+                                        Make a window ignore or receive the mouse
+  opacity <handle> [value]              Print or set the opacity (0.0 to 1.0)
+  top <handle> [on|off]                 Keep a window above others (default: on)
+  focus <handle>                        Bring a window to the foreground
+  close <handle>                        Ask a window to close
+  kill <handle>                         Terminate the process owning a window
+  state <handle> [state]                Print the state, or apply one of: minimize, maximize,
+                                        restore, fullscreen, exit-fullscreen, hide, show
+  watch                                 Print windows as they open, get renamed and close
+
+Options:
+  --json                                Print JSON instead of tables
+  -h, --help                            Show this help
+
+Handles are decimal or 0x-prefixed hexadecimal. Exit codes: 0 success,
+1 failure or no match, 2 usage error.";
+
+/// Like `println!`, but returns write errors instead of panicking, such as a pipe closed by `head`
+macro_rules! outln {
+  ($($arg:tt)*) => {
+    writeln!(io::stdout().lock(), $($arg)*)
+  };
+}
+
+/// Error carrying the exit code to report
+struct Failure {
+  code: i32,
+  message: String,
+}
+
+type CliResult = std::result::Result<(), Failure>;
+
+fn usage_error(message: impl Into<String>) -> Failure {
+  Failure {
+    code: EXIT_USAGE,
+    message: message.into(),
+  }
+}
+
+fn failure(message: impl Into<String>) -> Failure {
+  Failure {
+    code: EXIT_FAILURE,
+    message: message.into(),
+  }
+}
+
+impl From<napi::Error> for Failure {
+  fn from(e: napi::Error) -> Self {
+    failure(e.reason)
+  }
+}
+
+impl From<io::Error> for Failure {
+  fn from(e: io::Error) -> Self {
+    // The reader is gone (`clicktt list | head`), there is nobody left to report to
+    if e.kind() == ErrorKind::BrokenPipe {
+      Failure {
+        code: 0,
+        message: String::new(),
+      }
+    } else {
+      failure(format!("cannot write output: {}", e))
+    }
+  }
+}
+
+/// Run the command line (without the program name) and return the process exit code
+pub fn run(args: Vec<String>) -> i32 {
+  let json = args.iter().any(|a| a == "--json");
+  let mut args: Vec<String> = args.into_iter().filter(|a| a != "--json").collect();
+
+  if args.is_empty() {
+    eprintln!("{}", USAGE);
+    return EXIT_USAGE;
+  }
+  let command = if args.iter().any(|a| a == "-h" || a == "--help") {
+    String::from("--help")
+  } else {
+    args.remove(0)
+  };
+  let result = match command.as_str() {
+    "--help" => outln!("{}", USAGE).map_err(Failure::from),
+    "list" => list(&args, json),
+    "find" => find(&args, json),
+    "click-through" => click_through(&args, json),
+    "opacity" => opacity(&args, json),
+    "top" => top(&args),
+    "focus" => with_handle(&args, platform::focus_window),
+    "close" => with_handle(&args, platform::close_window),
+    "kill" => with_handle(&args, platform::kill_window_process),
+    "state" => state(&args, json),
+    "watch" => watch(&args, json),
+    _ => Err(usage_error(format!("unknown command '{}'", command))),
+  };
+
+  match result {
+    Ok(()) => 0,
+    Err(e) if e.code == 0 => 0,
+    Err(e) => {
+      eprintln!("clicktt: {}", e.message);
+      if e.code == EXIT_USAGE {
+        eprintln!("Run 'clicktt --help' for usage.");
+      }
+      e.code
+    }
+  }
+}
+
+/// Parse a decimal or 0x-prefixed hexadecimal window handle
+fn parse_handle(value: &str) -> std::result::Result<i64, Failure> {
+  let parsed = match value
+    .strip_prefix("0x")
+    .or_else(|| value.strip_prefix("0X"))
+  {
+    Some(hex) => i64::from_str_radix(hex, 16),
+    None => value.parse(),
+  };
+  parsed.map_err(|_| usage_error(format!("invalid window handle '{}'", value)))
+}
+
+/// Check the number of positional arguments
+fn expect_args(args: &[String], min: usize, max: usize) -> CliResult {
+  if args.len() < min {
+    Err(usage_error("missing argument"))
+  } else if args.len() > max {
+    Err(usage_error(format!("unexpected argument '{}'", args[max])))
+  } else {
+    Ok(())
+  }
+}
+
+/// Parse `on`/`off` style switches
+fn parse_switch(value: &str) -> std::result::Result<bool, Failure> {
+  match value.to_ascii_lowercase().as_str() {
+    "on" | "true" | "1" | "yes" => Ok(true),
+    "off" | "false" | "0" | "no" => Ok(false),
+    _ => Err(usage_error(format!("expected on or off, got '{}'", value))),
+  }
+}
+
+fn with_handle(args: &[String], action: fn(i64) -> napi::Result<()>) -> CliResult {
+  expect_args(args, 1, 1)?;
+  action(parse_handle(&args[0])?)?;
+  Ok(())
+}
+
+/// Quote a string for JSON output
+fn json_string(value: &str) -> String {
+  let mut out = String::with_capacity(value.len() + 2);
+  out.push('"');
+  for c in value.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+fn window_json(w: &WindowInfo) -> String {
+  format!(
+    "{{\"handle\":{},\"title\":{},\"processId\":{},\"className\":{},\"visible\":{},\"x\":{},\"y\":{},\"width\":{},\"height\":{},\"path\":{},\"zIndex\":{},\"desktop\":{},\"minimized\":{},\"maximized\":{},\"fullscreen\":{},\"focused\":{},\"alwaysOnTop\":{},\"windowType\":{}}}",
+    w.handle,
+    json_string(&w.title),
+    w.process_id,
+    json_string(&w.class_name),
+    w.visible,
+    w.x,
+    w.y,
+    w.width,
+    w.height,
+    json_string(&w.path),
+    w.z_index,
+    w.desktop,
+    w.minimized,
+    w.maximized,
+    w.fullscreen,
+    w.focused,
+    w.always_on_top,
+    json_string(&format!("{:?}", w.window_type)),
+  )
+}
+
+/// Print rows as left-aligned columns, the last column is never padded
+fn print_table(header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+  let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+  for row in rows {
+    for (width, cell) in widths.iter_mut().zip(row) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+
+  let line = |cells: Vec<String>| {
+    let last = cells.len() - 1;
+    let padded: Vec<String> = cells
+      .into_iter()
+      .enumerate()
+      .map(|(i, cell)| {
+        if i == last {
+          cell
+        } else {
+          format!("{:<width$}", cell, width = widths[i])
+        }
+      })
+      .collect();
+    outln!("{}", padded.join("  ").trim_end())
+  };
+
+  line(header.iter().map(|h| h.to_string()).collect())?;
+  for row in rows {
+    line(row.clone())?;
+  }
+  Ok(())
+}
+
+fn print_windows(windows: &[WindowInfo], json: bool) -> io::Result<()> {
+  if json {
+    let items: Vec<String> = windows.iter().map(window_json).collect();
+    return outln!("[{}]", items.join(","));
+  }
+
+  let rows: Vec<Vec<String>> = windows
+    .iter()
+    .map(|w| {
+      vec![
+        w.handle.to_string(),
+        w.process_id.to_string(),
+        format!("{},{}", w.x, w.y),
+        format!("{}x{}", w.width, w.height),
+        w.class_name.clone(),
+        w.title.clone(),
+      ]
+    })
+    .collect();
+  print_table(
+    &["HANDLE", "PID", "POSITION", "SIZE", "CLASS", "TITLE"],
+    &rows,
+  )
+}
+
+fn list(args: &[String], json: bool) -> CliResult {
+  let all = args.iter().any(|a| a == "--all");
+  let args: Vec<String> = args.iter().filter(|a| *a != "--all").cloned().collect();
+  expect_args(&args, 0, 0)?;
+
+  let mut windows = platform::get_windows()?;
+  windows.retain(|w| all || w.visible);
+  print_windows(&windows, json)?;
+  Ok(())
+}
+
+fn find(args: &[String], json: bool) -> CliResult {
+  let exact = args.iter().any(|a| a == "--exact");
+  let args: Vec<String> = args.iter().filter(|a| *a != "--exact").cloned().collect();
+  expect_args(&args, 1, 1)?;
+
+  let title = &args[0];
+  let mut windows = platform::get_windows()?;
+  windows.retain(|w| {
+    if exact {
+      w.title == *title
+    } else {
+      w.title.to_lowercase().contains(&title.to_lowercase())
+    }
+  });
+
+  print_windows(&windows, json)?;
+  if windows.is_empty() {
+    return Err(failure(format!("no window matches '{}'", title)));
+  }
+  Ok(())
+}
+
+fn click_through(args: &[String], json: bool) -> CliResult {
+  expect_args(args, 2, 2)?;
+  let handle = parse_handle(&args[1])?;
+
+  let enabled = match args[0].as_str() {
+    "toggle" => platform::toggle_click_through(handle)?,
+    value => {
+      let enable = parse_switch(value)?;
+      platform::set_click_through(handle, enable)?;
+      enable
+    }
+  };
+
+  if json {
+    outln!("{{\"clickThrough\":{}}}", enabled)?;
+  } else {
+    outln!("{}", if enabled { "on" } else { "off" })?;
+  }
+  Ok(())
+}
+
+fn opacity(args: &[String], json: bool) -> CliResult {
+  expect_args(args, 1, 2)?;
+  let handle = parse_handle(&args[0])?;
+
+  if let Some(value) = args.get(1) {
+    let opacity: f64 = value
+      .parse()
+      .ok()
+      .filter(|v: &f64| (0.0..=1.0).contains(v))
+      .ok_or_else(|| usage_error(format!("opacity must be between 0 and 1, got '{}'", value)))?;
+    platform::set_window_opacity(handle, opacity)?;
+    return Ok(());
+  }
+
+  let opacity = platform::get_window_opacity(handle)?;
+  if json {
+    outln!("{{\"opacity\":{}}}", opacity)?;
+  } else {
+    outln!("{:.2}", opacity)?;
+  }
+  Ok(())
+}
+
+fn top(args: &[String]) -> CliResult {
+  expect_args(args, 1, 2)?;
+  let handle = parse_handle(&args[0])?;
+  let on_top = args.get(1).map(|v| parse_switch(v)).transpose()?;
+  platform::set_always_on_top(handle, on_top.unwrap_or(true))?;
+  Ok(())
+}
+
+fn parse_state(value: &str) -> std::result::Result<WindowState, Failure> {
+  match value.to_ascii_lowercase().as_str() {
+    "minimize" => Ok(WindowState::Minimize),
+    "maximize" => Ok(WindowState::Maximize),
+    "restore" => Ok(WindowState::Restore),
+    "fullscreen" => Ok(WindowState::Fullscreen),
+    "exit-fullscreen" => Ok(WindowState::ExitFullscreen),
+    "hide" => Ok(WindowState::Hide),
+    "show" => Ok(WindowState::Show),
+    _ => Err(usage_error(format!("unknown window state '{}'", value))),
+  }
+}
+
+fn print_state(state: &WindowStateInfo, json: bool) -> io::Result<()> {
+  let fields = [
+    ("minimized", state.minimized),
+    ("maximized", state.maximized),
+    ("fullscreen", state.fullscreen),
+    ("focused", state.focused),
+    ("alwaysOnTop", state.always_on_top),
+  ];
+
+  if json {
+    let items: Vec<String> = fields
+      .iter()
+      .map(|(name, value)| format!("\"{}\":{}", name, value))
+      .collect();
+    outln!("{{{}}}", items.join(","))
+  } else {
+    let rows: Vec<Vec<String>> = fields
+      .iter()
+      .map(|(name, value)| vec![name.to_string(), value.to_string()])
+      .collect();
+    print_table(&["STATE", "VALUE"], &rows)
+  }
+}
+
+fn state(args: &[String], json: bool) -> CliResult {
+  expect_args(args, 1, 2)?;
+  let handle = parse_handle(&args[0])?;
+
+  if let Some(value) = args.get(1) {
+    platform::set_window_state(handle, parse_state(value)?)?;
+    return Ok(());
+  }
+
+  let state = platform::get_window_state(handle)?.ok_or_else(|| failure("window not found"))?;
+  print_state(&state, json)?;
+  Ok(())
+}
+
+fn watch(args: &[String], json: bool) -> CliResult {
+  expect_args(args, 0, 0)?;
+
+  // Titles of the known windows, to tell openings from renames and name closed windows
+  let mut titles: HashMap<i64, String> = platform::get_windows()?
+    .into_iter()
+    .map(|w| (w.handle, w.title))
+    .collect();
+
+  let print = |event: &str, handle: i64, title: &str| {
+    if json {
+      outln!(
+        "{{\"event\":\"{}\",\"handle\":{},\"title\":{}}}",
+        event,
+        handle,
+        json_string(title)
+      )
+    } else {
+      outln!("{:<8}  {:<12}  {}", event, handle, title)
+    }
+  };
+
+  let stop = AtomicBool::new(false);
+  let (ready, started) = mpsc::channel();
+  let mut write_error = None;
+
+  platform::watch_windows(&stop, ready, |event| {
+    let printed = match event {
+      WindowEvent::Changed(handle) => {
+        let Ok(Some(window)) = platform::get_window_info(handle) else {
+          return;
+        };
+        if !window.visible || window.title.is_empty() {
+          return;
+        }
+        match titles.insert(handle, window.title.clone()) {
+          None => print("opened", handle, &window.title),
+          Some(previous) if previous != window.title => print("renamed", handle, &window.title),
+          Some(_) => Ok(()),
+        }
+      }
+      WindowEvent::Closed(handle) => match titles.remove(&handle) {
+        Some(title) => print("closed", handle, &title),
+        None => Ok(()),
+      },
+    };
+
+    // Stop once the output is gone
+    if let Err(e) = printed {
+      write_error = Some(e);
+      stop.store(true, Ordering::Relaxed);
+    }
+  });
+
+  // Otherwise the watcher only returns when it could not start
+  if let Some(e) = write_error {
+    return Err(e.into());
+  }
+  started.try_recv().unwrap_or(Ok(()))?;
+  Ok(())
+}
@@ -0,0 +1,142 @@
+use napi::bindgen_prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{platform, Easing};
+
+/// Time between two opacity updates (~60 fps)
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Source of unique animation ids, never reused across windows
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    /// Latest animation started on each window; older ones stop when they see a newer value
+    static ref GENERATIONS: Mutex<HashMap<i64, u64>> = Mutex::new(HashMap::new());
+}
+
+fn ease(easing: Easing, t: f64) -> f64 {
+  match easing {
+    Easing::Linear => t,
+    Easing::EaseIn => t * t,
+    Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+    Easing::EaseInOut => {
+      if t < 0.5 {
+        2.0 * t * t
+      } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+      }
+    }
+  }
+}
+
+/// Whether `generation` is still the latest animation of the window
+fn is_current(handle: i64, generation: u64) -> bool {
+  GENERATIONS
+    .lock()
+    .map(|g| g.get(&handle) == Some(&generation))
+    .unwrap_or(false)
+}
+
+/// Animate the opacity of a window towards `target` in a background thread
+/// `done` receives true once the target is reached, false if a newer animation took over
+pub fn start(
+  handle: i64,
+  target: f64,
+  duration: Duration,
+  easing: Easing,
+  done: impl FnOnce(Result<bool>) + Send + 'static,
+) -> Result<()> {
+  let from = platform::get_window_opacity(handle)?;
+
+  let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+  GENERATIONS
+    .lock()
+    .map_err(|_| Error::new(Status::GenericFailure, "Fade state is poisoned"))?
+    .insert(handle, generation);
+
+  thread::spawn(move || {
+    let started = Instant::now();
+
+    let result = loop {
+      if !is_current(handle, generation) {
+        break Ok(false);
+      }
+
+      let t = if duration.is_zero() {
+        1.0
+      } else {
+        (started.elapsed().as_secs_f64() / duration.as_secs_f64()).min(1.0)
+      };
+      let opacity = from + (target - from) * ease(easing, t);
+      if let Err(e) = platform::set_window_opacity(handle, opacity) {
+        break Err(e);
+      }
+
+      if t >= 1.0 {
+        break Ok(true);
+      }
+      thread::sleep(FRAME_INTERVAL);
+    };
+
+    // Forget finished animations unless a newer one replaced them
+    if let Ok(mut generations) = GENERATIONS.lock() {
+      if generations.get(&handle) == Some(&generation) {
+        generations.remove(&handle);
+      }
+    }
+
+    done(result);
+  });
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ALL: [Easing; 4] = [
+    Easing::Linear,
+    Easing::EaseIn,
+    Easing::EaseOut,
+    Easing::EaseInOut,
+  ];
+
+  fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+  }
+
+  #[test]
+  fn ease_starts_at_zero_and_ends_at_one() {
+    for easing in ALL {
+      assert!(close(ease(easing, 0.0), 0.0));
+      assert!(close(ease(easing, 1.0), 1.0));
+    }
+  }
+
+  #[test]
+  fn ease_is_monotonic() {
+    for easing in ALL {
+      let mut previous = 0.0;
+      for step in 1..=100 {
+        let value = ease(easing, step as f64 / 100.0);
+        assert!(value >= previous);
+        previous = value;
+      }
+    }
+  }
+
+  #[test]
+  fn ease_curves_at_midpoints() {
+    assert!(close(ease(Easing::Linear, 0.25), 0.25));
+    assert!(close(ease(Easing::EaseIn, 0.5), 0.25));
+    assert!(close(ease(Easing::EaseOut, 0.5), 0.75));
+    assert!(close(ease(Easing::EaseInOut, 0.5), 0.5));
+    assert!(close(ease(Easing::EaseInOut, 0.25), 0.125));
+    assert!(close(ease(Easing::EaseInOut, 0.75), 0.875));
+  }
+}
@@ -0,0 +1,152 @@
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::{platform, utils};
+
+pub type HotkeyCallback = ThreadsafeFunction<String, (), String, Status, false>;
+
+/// Requests handled by the platform hotkey thread
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+pub enum HotkeyCommand {
+  Register {
+    id: u32,
+    modifiers: u32,
+    key: String,
+    reply: Sender<Result<()>>,
+  },
+  Unregister {
+    id: u32,
+  },
+}
+
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+struct Hotkey {
+  id: u32,
+  accelerator: String,
+  callback: HotkeyCallback,
+}
+
+#[derive(Default)]
+struct Registry {
+  next_id: u32,
+  /// Registered hotkeys by normalized accelerator
+  hotkeys: HashMap<String, Hotkey>,
+  /// Channel to the platform thread, spawned on first registration
+  commands: Option<Sender<HotkeyCommand>>,
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+fn lock_registry() -> Result<std::sync::MutexGuard<'static, Registry>> {
+  REGISTRY
+    .lock()
+    .map_err(|_| Error::new(Status::GenericFailure, "Hotkey state is poisoned"))
+}
+
+/// Normalize an accelerator so `alt+ctrl+f9` and `Ctrl+Alt+F9` are the same hotkey
+fn normalize(modifiers: u32, key: &str) -> String {
+  let mut parts = utils::modifier_names(modifiers);
+  parts.push(key.to_ascii_lowercase());
+  parts.join("+")
+}
+
+fn already_registered(accelerator: &str) -> Error {
+  Error::new(
+    Status::InvalidArg,
+    format!("Hotkey {} is already registered", accelerator),
+  )
+}
+
+/// Grab a global hotkey, `callback` receives the accelerator on every press
+pub fn register(accelerator: &str, callback: HotkeyCallback) -> Result<()> {
+  let (modifiers, key) = utils::parse_accelerator(accelerator)?;
+  let normalized = normalize(modifiers, &key);
+
+  let (commands, id) = {
+    let mut registry = lock_registry()?;
+    if registry.hotkeys.contains_key(&normalized) {
+      return Err(already_registered(accelerator));
+    }
+
+    let commands = registry
+      .commands
+      .get_or_insert_with(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || platform::run_hotkey_loop(receiver));
+        sender
+      })
+      .clone();
+
+    registry.next_id += 1;
+    (commands, registry.next_id)
+  };
+
+  // Wait for the grab without holding the registry, which `dispatch` needs
+  let (reply, result) = mpsc::channel();
+  commands
+    .send(HotkeyCommand::Register {
+      id,
+      modifiers,
+      key,
+      reply,
+    })
+    .map_err(|_| Error::new(Status::GenericFailure, "Hotkey thread is not running"))?;
+  platform::wake_hotkey_loop();
+  result
+    .recv()
+    .map_err(|_| Error::new(Status::GenericFailure, "Hotkey thread is not running"))?
+    .map_err(|e| Error::new(e.status, format!("{}: {}", accelerator, e.reason)))?;
+
+  let mut registry = lock_registry()?;
+  if registry.hotkeys.contains_key(&normalized) {
+    // Registered concurrently from another call
+    let _ = commands.send(HotkeyCommand::Unregister { id });
+    platform::wake_hotkey_loop();
+    return Err(already_registered(accelerator));
+  }
+
+  registry.hotkeys.insert(
+    normalized,
+    Hotkey {
+      id,
+      accelerator: accelerator.to_string(),
+      callback,
+    },
+  );
+  Ok(())
+}
+
+/// Release a global hotkey. Returns `false` if it was not registered.
+pub fn unregister(accelerator: &str) -> Result<bool> {
+  let (modifiers, key) = utils::parse_accelerator(accelerator)?;
+
+  let mut registry = lock_registry()?;
+  let Some(hotkey) = registry.hotkeys.remove(&normalize(modifiers, &key)) else {
+    return Ok(false);
+  };
+
+  if let Some(commands) = &registry.commands {
+    let _ = commands.send(HotkeyCommand::Unregister { id: hotkey.id });
+    platform::wake_hotkey_loop();
+  }
+  Ok(true)
+}
+
+/// Called from the platform thread when the hotkey `id` is pressed
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+pub fn dispatch(id: u32) {
+  if let Ok(registry) = REGISTRY.lock() {
+    if let Some(hotkey) = registry.hotkeys.values().find(|h| h.id == id) {
+      hotkey.callback.call(
+        hotkey.accelerator.clone(),
+        ThreadsafeFunctionCallMode::NonBlocking,
+      );
+    }
+  }
+}
@@ -0,0 +1,98 @@
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::{platform, InteractEvent};
+
+/// How often the keyboard state is sampled
+const POLL_INTERVAL: Duration = Duration::from_millis(30);
+
+pub type InteractCallback = ThreadsafeFunction<InteractEvent, (), InteractEvent, Status, false>;
+
+lazy_static::lazy_static! {
+    /// Stop flags of the running watchers, by window handle
+    static ref WATCHERS: Mutex<HashMap<i64, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+/// Keep a window click-through, lifting it while all `modifiers` are held
+pub fn enable(handle: i64, modifiers: u32, callback: Option<InteractCallback>) -> Result<()> {
+  // Fail early on platforms that cannot read the keyboard state
+  platform::get_pressed_modifiers()?;
+
+  disable(handle);
+  platform::set_click_through(handle, true)?;
+
+  let stop = Arc::new(AtomicBool::new(false));
+  WATCHERS
+    .lock()
+    .map_err(|_| Error::new(Status::GenericFailure, "Interact state is poisoned"))?
+    .insert(handle, stop.clone());
+
+  thread::spawn(move || {
+    let mut interactive = false;
+
+    while !stop.load(Ordering::Relaxed) {
+      let held = platform::get_pressed_modifiers()
+        .map(|pressed| pressed & modifiers == modifiers)
+        .unwrap_or(false);
+
+      if held != interactive {
+        // The window is most likely gone, nothing left to watch
+        if platform::set_click_through(handle, !held).is_err() {
+          break;
+        }
+        interactive = held;
+
+        if let Some(callback) = &callback {
+          callback.call(
+            InteractEvent {
+              handle,
+              interactive,
+            },
+            ThreadsafeFunctionCallMode::NonBlocking,
+          );
+        }
+      }
+
+      thread::sleep(POLL_INTERVAL);
+    }
+
+    // Leave the window click-through once the watcher stops
+    if interactive {
+      let _ = platform::set_click_through(handle, true);
+    }
+
+    forget(handle, &stop);
+  });
+
+  Ok(())
+}
+
+/// Remove the watcher of `handle` unless a newer one replaced it
+fn forget(handle: i64, stop: &Arc<AtomicBool>) {
+  if let Ok(mut watchers) = WATCHERS.lock() {
+    if watchers.get(&handle).is_some_and(|s| Arc::ptr_eq(s, stop)) {
+      watchers.remove(&handle);
+    }
+  }
+}
+
+/// Stop watching the modifiers of a window. Returns whether a watcher was running.
+pub fn disable(handle: i64) -> bool {
+  let stop = WATCHERS
+    .lock()
+    .ok()
+    .and_then(|mut watchers| watchers.remove(&handle));
+
+  match stop {
+    Some(stop) => {
+      stop.store(true, Ordering::Relaxed);
+      true
+    }
+    None => false,
+  }
+}
@@ -0,0 +1,157 @@
+use napi::bindgen_prelude::*;
+
+use crate::platform::{self, WindowState};
+use crate::{utils, Layout, LayoutEntry, RestoreLayoutResult, WindowInfo, WindowMatch};
+
+/// Stable identifiers of a window: class and executable, plus the title to break ties
+/// None when the window has none of them, as empty criteria would match any window
+fn identify(window: &WindowInfo) -> Option<WindowMatch> {
+  let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+  let criteria = WindowMatch {
+    title: non_empty(&window.title),
+    class_name: non_empty(&window.class_name),
+    path: non_empty(&window.path),
+    ..Default::default()
+  };
+
+  (criteria.title.is_some() || criteria.class_name.is_some() || criteria.path.is_some())
+    .then_some(criteria)
+}
+
+/// Save the placement of the visible windows matching `query` (all when None)
+pub fn capture(query: Option<&WindowMatch>) -> Result<Layout> {
+  let mut windows: Vec<WindowInfo> = platform::get_windows()?
+    .into_iter()
+    .filter(|w| w.visible && query.is_none_or(|q| utils::window_matches(q, w)))
+    .collect();
+  windows.sort_by_key(|w| w.z_index);
+
+  // Windows that cannot be told apart from others are left out
+  let windows = windows
+    .iter()
+    .filter_map(|w| Some((w, identify(w)?)))
+    .map(|(w, window_match)| LayoutEntry {
+      window_match,
+      bounds: platform::get_restored_bounds(w.handle)
+        .ok()
+        .flatten()
+        .unwrap_or(crate::Rect {
+          x: w.x,
+          y: w.y,
+          width: w.width,
+          height: w.height,
+        }),
+      minimized: w.minimized,
+      maximized: w.maximized,
+      fullscreen: w.fullscreen,
+      desktop: w.desktop,
+      opacity: platform::get_window_opacity(w.handle).unwrap_or(1.0),
+      always_on_top: w.always_on_top,
+    })
+    .collect();
+
+  Ok(Layout { windows })
+}
+
+/// Criteria used once the title no longer matches, None when only the title identifies the window
+fn without_title(criteria: &WindowMatch) -> Option<WindowMatch> {
+  (criteria.class_name.is_some() || criteria.path.is_some()).then(|| WindowMatch {
+    title: None,
+    ..criteria.clone()
+  })
+}
+
+/// Reapply the saved placement and state to a window
+fn apply(window: &WindowInfo, entry: &LayoutEntry) -> Result<()> {
+  let handle = window.handle;
+
+  // Bounds only stick on a normal window, the saved state is applied afterwards
+  if window.fullscreen {
+    platform::set_window_state(handle, WindowState::ExitFullscreen)?;
+  }
+  if window.maximized || window.minimized {
+    platform::set_window_state(handle, WindowState::Restore)?;
+  }
+  platform::set_window_bounds(handle, entry.bounds, false, false)?;
+
+  if entry.fullscreen {
+    platform::set_window_state(handle, WindowState::Fullscreen)?;
+  } else if entry.maximized {
+    platform::set_window_state(handle, WindowState::Maximize)?;
+  } else if entry.minimized {
+    platform::set_window_state(handle, WindowState::Minimize)?;
+  }
+
+  if entry.desktop >= 0 && entry.desktop != window.desktop {
+    platform::move_window_to_desktop(handle, entry.desktop as u32)?;
+  }
+
+  let opacity = platform::get_window_opacity(handle)?;
+  if (opacity - entry.opacity).abs() > f64::EPSILON {
+    if entry.opacity >= 1.0 {
+      platform::remove_window_opacity(handle)?;
+    } else {
+      platform::set_window_opacity(handle, entry.opacity.clamp(0.0, 1.0))?;
+    }
+  }
+
+  if entry.always_on_top != window.always_on_top {
+    platform::set_always_on_top(handle, entry.always_on_top)?;
+  }
+
+  Ok(())
+}
+
+/// Match each entry to a current window (each window used once) and reapply its placement
+pub fn restore(layout: Layout) -> Result<RestoreLayoutResult> {
+  let mut windows = platform::get_windows()?;
+  let mut matched: Vec<Option<WindowInfo>> = layout.windows.iter().map(|_| None).collect();
+
+  // Titles are matched first so a renamed window cannot take the place of one that kept its title
+  for (entry, slot) in layout.windows.iter().zip(matched.iter_mut()) {
+    if let Some(index) = windows
+      .iter()
+      .position(|w| utils::window_matches(&entry.window_match, w))
+    {
+      *slot = Some(windows.remove(index));
+    }
+  }
+  for (entry, slot) in layout.windows.iter().zip(matched.iter_mut()) {
+    let Some(identity) = slot
+      .is_none()
+      .then(|| without_title(&entry.window_match))
+      .flatten()
+    else {
+      continue;
+    };
+    if let Some(index) = windows
+      .iter()
+      .position(|w| utils::window_matches(&identity, w))
+    {
+      *slot = Some(windows.remove(index));
+    }
+  }
+
+  let mut result = RestoreLayoutResult {
+    restored: Vec::new(),
+    unmatched: Vec::new(),
+    errors: Vec::new(),
+  };
+
+  for (entry, window) in layout.windows.into_iter().zip(matched) {
+    let Some(window) = window else {
+      result.unmatched.push(entry);
+      continue;
+    };
+
+    match apply(&window, &entry) {
+      Ok(()) => result.restored.push(window.handle),
+      Err(e) => result.errors.push(format!(
+        "{} ({}): {}",
+        window.title, window.handle, e.reason
+      )),
+    }
+  }
+
+  Ok(result)
+}
@@ -1,6 +1,15 @@
 #![deny(clippy::all)]
 
+mod capture;
+#[cfg(feature = "cli")]
+pub mod cli;
+mod fade;
+mod hotkey;
+mod interact;
+mod layout;
+mod overlay;
 mod platform;
+mod rules;
 mod utils;
 
 use napi::bindgen_prelude::*;
@@ -16,7 +25,7 @@ pub struct WindowInfo {
   pub title: String,
   /// Process ID that owns the window
   pub process_id: u32,
-  /// Window class name (Windows only, empty on other platforms)
+  /// Window class name (WM_CLASS class on X11, empty on macOS)
   pub class_name: String,
   /// Whether the window is visible
   pub visible: bool,
@@ -30,13 +39,405 @@ pub struct WindowInfo {
   pub height: i32,
   /// Path to the executable process that owns the window
   pub path: String,
+  /// Position in the stacking order, counted from the bottom (-1 when unknown)
+  pub z_index: i32,
+  /// Virtual desktop the window is on (-1 when shown on all desktops or unknown)
+  pub desktop: i32,
+  /// Whether the window is minimized (iconic)
+  pub minimized: bool,
+  /// Whether the window is maximized
+  pub maximized: bool,
+  /// Whether the window is fullscreen
+  pub fullscreen: bool,
+  /// Whether the window has the input focus
+  pub focused: bool,
+  /// Whether the window is kept above other windows
+  pub always_on_top: bool,
+  /// Window type hint (EWMH `_NET_WM_WINDOW_TYPE` on X11)
+  pub window_type: WindowType,
+}
+
+/// Represents the current state of a window
+#[napi(object)]
+#[derive(Clone, Copy, Default)]
+pub struct WindowStateInfo {
+  /// Whether the window is minimized (iconic)
+  pub minimized: bool,
+  /// Whether the window is maximized
+  pub maximized: bool,
+  /// Whether the window is fullscreen
+  pub fullscreen: bool,
+  /// Whether the window has the input focus
+  pub focused: bool,
+  /// Whether the window is kept above other windows
+  pub always_on_top: bool,
+}
+
+/// Represents a rectangle in screen coordinates
+#[napi(object)]
+#[derive(Clone, Copy)]
+pub struct Rect {
+  /// Position X
+  pub x: i32,
+  /// Position Y
+  pub y: i32,
+  /// Width
+  pub width: i32,
+  /// Height
+  pub height: i32,
+}
+
+impl Rect {
+  /// Returns the overlapping part of two rectangles, if any
+  pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+    let left = self.x.max(other.x);
+    let top = self.y.max(other.y);
+    let right = (self.x + self.width).min(other.x + other.width);
+    let bottom = (self.y + self.height).min(other.y + other.height);
+
+    if right > left && bottom > top {
+      Some(Rect {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+      })
+    } else {
+      None
+    }
+  }
+
+  /// Clip a rectangle positioned relative to `(x, y)` to `bounds`, keeping it relative
+  pub fn clip_relative(&self, x: i32, y: i32, bounds: &Rect) -> Option<Rect> {
+    let absolute = Rect {
+      x: self.x + x,
+      y: self.y + y,
+      ..*self
+    };
+    absolute.intersection(bounds).map(|clipped| Rect {
+      x: clipped.x - x,
+      y: clipped.y - y,
+      ..clipped
+    })
+  }
+
+  /// Whether the point lies inside the rectangle
+  pub fn contains(&self, x: i32, y: i32) -> bool {
+    x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+  }
+}
+
+/// Pointer position and button/modifier state
+#[napi(object)]
+#[derive(Clone)]
+pub struct CursorPosition {
+  /// Position X in virtual screen coordinates
+  pub x: i32,
+  /// Position Y in virtual screen coordinates
+  pub y: i32,
+  /// Monitor under the pointer
+  pub monitor: Option<MonitorInfo>,
+  /// Whether the left mouse button is held
+  pub left_button: bool,
+  /// Whether the middle mouse button is held
+  pub middle_button: bool,
+  /// Whether the right mouse button is held
+  pub right_button: bool,
+  /// Modifier keys held (`Shift`, `Ctrl`, `Alt`, `Super`)
+  pub modifiers: Vec<String>,
+}
+
+/// Options for `captureWindow`
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct CaptureOptions {
+  /// Area to capture, relative to the captured window (default: everything)
+  pub region: Option<Rect>,
+  /// Capture the window manager frame too instead of the client area only (default: false)
+  pub include_frame: Option<bool>,
+}
+
+/// Options for `startWindowCapture`
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowCaptureOptions {
+  /// Maximum frames per second delivered (default: 30)
+  pub max_fps: Option<f64>,
+  /// Area to capture, relative to the captured window (default: everything)
+  pub region: Option<Rect>,
+  /// Capture the window manager frame too instead of the client area only (default: false)
+  pub include_frame: Option<bool>,
+}
+
+/// An RGBA image, such as a window capture or icon
+#[napi(object)]
+pub struct RgbaImage {
+  /// Image width in pixels
+  pub width: u32,
+  /// Image height in pixels
+  pub height: u32,
+  /// Pixels as tightly packed RGBA, row by row from the top
+  pub data: Buffer,
+}
+
+/// Options for `getWindowAtPoint`
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowAtPointOptions {
+  /// Windows to look through, such as your own overlays
+  pub skip_handles: Option<Vec<i64>>,
+}
+
+/// Criteria a window must meet, every field that is set must match
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowMatch {
+  /// Part of the title (case-insensitive)
+  pub title: Option<String>,
+  /// Window class name (case-insensitive)
+  pub class_name: Option<String>,
+  /// Executable path, or only its file name (case-insensitive)
+  pub path: Option<String>,
+  /// Process ID that owns the window
+  pub process_id: Option<u32>,
+  /// Window type hint
+  pub window_type: Option<WindowType>,
+}
+
+/// A window saved by `captureLayout`
+#[napi(object)]
+#[derive(Clone)]
+pub struct LayoutEntry {
+  /// Identifies the window on restore; the title only breaks ties when the class or path is known
+  #[napi(js_name = "match")]
+  pub window_match: WindowMatch,
+  /// Outer frame bounds
+  pub bounds: Rect,
+  /// Whether the window was minimized
+  pub minimized: bool,
+  /// Whether the window was maximized
+  pub maximized: bool,
+  /// Whether the window was fullscreen
+  pub fullscreen: bool,
+  /// Virtual desktop (-1 when shown on all desktops or unknown)
+  pub desktop: i32,
+  /// Window opacity (0.0 to 1.0)
+  pub opacity: f64,
+  /// Whether the window was kept above other windows
+  pub always_on_top: bool,
+}
+
+/// Saved window placements, plain data that can go through `JSON.stringify`
+#[napi(object)]
+#[derive(Clone)]
+pub struct Layout {
+  /// Saved windows, from the bottom to the top of the stacking order
+  pub windows: Vec<LayoutEntry>,
+}
+
+/// Outcome of `restoreLayout`
+#[napi(object)]
+pub struct RestoreLayoutResult {
+  /// Handles of the windows that were restored
+  pub restored: Vec<i64>,
+  /// Entries no current window matched
+  pub unmatched: Vec<LayoutEntry>,
+  /// Windows that matched but could not be fully restored, with the reason
+  pub errors: Vec<String>,
+}
+
+/// Changes applied by a window rule, every field that is set is applied
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowRuleActions {
+  /// Window opacity (0.0 to 1.0)
+  pub opacity: Option<f64>,
+  /// Keep the window above other windows
+  pub always_on_top: Option<bool>,
+  /// Window manager flags to add (`true`) or remove (`false`), such as `sticky`
+  pub flags: Option<WindowFlags>,
+  /// Make the window click-through
+  pub click_through: Option<bool>,
+  /// Show or strip the title bar and borders
+  pub decorations: Option<bool>,
+  /// Move the window to this monitor (index in `getMonitors()`), keeping its offset in the work area
+  pub monitor: Option<u32>,
+  /// Move and resize the window (outer frame), takes precedence over `monitor`
+  pub bounds: Option<Rect>,
+  /// Move the window to this virtual desktop
+  pub desktop: Option<u32>,
+  /// Window state to apply
+  pub state: Option<WindowState>,
+}
+
+/// Options for `addWindowRule`
+#[napi(object)]
+#[derive(Clone)]
+pub struct WindowRuleOptions {
+  /// Windows the rule applies to
+  #[napi(js_name = "match")]
+  pub window_match: WindowMatch,
+  /// Changes applied to each window when it starts matching
+  pub actions: WindowRuleActions,
+  /// Remove the rule after it applied to a first window (default: false)
+  pub once: Option<bool>,
+}
+
+/// A rule registered with `addWindowRule`
+#[napi(object)]
+#[derive(Clone)]
+pub struct WindowRule {
+  /// Rule id, used by `removeWindowRule`
+  pub id: u32,
+  /// Windows the rule applies to
+  #[napi(js_name = "match")]
+  pub window_match: WindowMatch,
+  /// Changes applied to each window when it starts matching
+  pub actions: WindowRuleActions,
+  /// Whether the rule is removed after it applied to a first window
+  pub once: bool,
+}
+
+/// A point in screen coordinates
+#[napi(object)]
+#[derive(Clone, Copy, Default)]
+pub struct Point {
+  pub x: i32,
+  pub y: i32,
+}
+
+/// Options for `attachOverlay`
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct OverlayOptions {
+  /// Point of the target's client area the overlay is aligned to (default: TopLeft)
+  pub anchor: Option<OverlayAnchor>,
+  /// Offset added to the anchored position (default: none)
+  pub offset: Option<Point>,
+  /// Resize the overlay to the target's client area (default: false)
+  pub match_size: Option<bool>,
+  /// Hide the overlay while the target is minimized (default: true)
+  pub hide_when_target_minimized: Option<bool>,
+  /// Hide the overlay while neither the target nor the overlay has the focus (default: false)
+  pub hide_when_target_unfocused: Option<bool>,
+}
+
+/// Represents information about a monitor (display output)
+#[napi(object)]
+#[derive(Clone)]
+pub struct MonitorInfo {
+  /// Output name (e.g. "HDMI-1" on X11, "\\.\DISPLAY1" on Windows)
+  pub name: String,
+  /// Full monitor bounds in virtual screen coordinates
+  pub bounds: Rect,
+  /// Usable area, excluding panels, docks and taskbars
+  pub work_area: Rect,
+  /// Whether this is the primary monitor
+  pub primary: bool,
+  /// Refresh rate in Hz (0 when unknown)
+  pub refresh_rate: f64,
+  /// Scale factor relative to 96 DPI (1.0 = 100%)
+  pub scale_factor: f64,
+}
+
+/// Represents a virtual desktop (workspace)
+#[napi(object)]
+#[derive(Clone)]
+pub struct DesktopInfo {
+  /// Desktop index
+  pub index: u32,
+  /// Desktop name (empty if the window manager does not name desktops)
+  pub name: String,
+  /// Whether this is the desktop currently shown
+  pub current: bool,
+}
+
+/// Window manager flags (EWMH `_NET_WM_STATE` on X11)
+/// When setting, `true` adds a flag, `false` removes it and omitted flags are left untouched
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowFlags {
+  /// Show the window on all virtual desktops (X11 only)
+  pub sticky: Option<bool>,
+  /// Hide the window from the taskbar
+  pub skip_taskbar: Option<bool>,
+  /// Hide the window from pagers and workspace switchers (X11 only)
+  pub skip_pager: Option<bool>,
+  /// Cover the whole monitor without decorations (X11 only)
+  pub fullscreen: Option<bool>,
+  /// Keep the window below other windows
+  pub below: Option<bool>,
+  /// Keep the window above other windows
+  pub above: Option<bool>,
+  /// Ask for the user's attention (flashes the taskbar entry)
+  pub demands_attention: Option<bool>,
+}
+
+/// Options for moving and resizing a window
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct WindowBoundsOptions {
+  /// Interpret the bounds as the client area instead of the outer frame (default: false)
+  pub client_area: Option<bool>,
+  /// Allow the window manager to animate the change (Windows only, default: false)
+  pub animate: Option<bool>,
 }
 
 #[napi]
+#[derive(Clone, Copy)]
 pub enum WindowState {
   Minimize,
   Maximize,
   Restore,
+  Fullscreen,
+  ExitFullscreen,
+  Hide,
+  Show,
+}
+
+/// Easing curves for `fadeWindowOpacity`
+#[napi]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+  Linear,
+  EaseIn,
+  EaseOut,
+  EaseInOut,
+}
+
+/// Where an attached overlay sits relative to its target
+#[napi]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OverlayAnchor {
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight,
+}
+
+/// Window type hints telling the window manager how to decorate, focus and stack a window
+#[napi]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowType {
+  Normal,
+  Desktop,
+  Dock,
+  Toolbar,
+  Menu,
+  Utility,
+  Splash,
+  Dialog,
+  DropdownMenu,
+  PopupMenu,
+  Tooltip,
+  Notification,
+  Combo,
+  Dnd,
 }
 
 impl From<WindowState> for platform::WindowState {
@@ -45,6 +446,10 @@ impl From<WindowState> for platform::WindowState {
       WindowState::Minimize => platform::WindowState::Minimize,
       WindowState::Maximize => platform::WindowState::Maximize,
       WindowState::Restore => platform::WindowState::Restore,
+      WindowState::Fullscreen => platform::WindowState::Fullscreen,
+      WindowState::ExitFullscreen => platform::WindowState::ExitFullscreen,
+      WindowState::Hide => platform::WindowState::Hide,
+      WindowState::Show => platform::WindowState::Show,
     }
   }
 }
@@ -78,6 +483,77 @@ pub fn is_click_through(handle: Unknown) -> Result<bool> {
   platform::is_click_through(handle_val)
 }
 
+/// Make only the given rectangles (window-relative) receive mouse input
+/// An empty array makes the window fully click-through, `null` fully interactive
+/// Partial regions need X11, Windows only accepts an empty array or `null`
+#[napi(js_name = "setInputRegion")]
+pub fn set_input_region(handle: Unknown, rects: Option<Vec<Rect>>) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::set_input_region(handle_val, rects.as_deref())
+}
+
+/// Make the transparent pixels of an RGBA image click-through
+/// Pixels whose alpha is above `alphaThreshold` (default 0) stay interactive
+/// X11 only, on Windows any mask with opaque pixels is rejected
+#[napi(js_name = "setInputMaskFromImage")]
+pub fn set_input_mask_from_image(
+  handle: Unknown,
+  rgba_buffer: Buffer,
+  width: u32,
+  height: u32,
+  alpha_threshold: Option<u32>,
+) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  let threshold = alpha_threshold.unwrap_or(0).min(255) as u8;
+  let rects = utils::alpha_mask_to_rects(&rgba_buffer, width, height, threshold)?;
+  platform::set_input_region(handle_val, Some(&rects))
+}
+
+/// Emitted when an interact-on-modifier window switches between click-through and interactive
+#[napi(object)]
+#[derive(Clone, Copy)]
+pub struct InteractEvent {
+  /// Window handle
+  pub handle: i64,
+  /// Whether the window currently receives mouse input
+  pub interactive: bool,
+}
+
+/// Keep a window click-through, making it interactive while `modifiers` (e.g. `Ctrl+Alt`) are held
+/// `callback` receives an `InteractEvent` on every transition
+#[napi(js_name = "enableInteractOnModifier")]
+pub fn enable_interact_on_modifier(
+  handle: Unknown,
+  modifiers: String,
+  callback: Option<interact::InteractCallback>,
+) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  let mask = utils::parse_modifiers(&modifiers)?;
+  interact::enable(handle_val, mask, callback)
+}
+
+/// Stop watching the modifiers of a window, which is left click-through
+/// Returns `false` if interact mode was not enabled
+#[napi(js_name = "disableInteractOnModifier")]
+pub fn disable_interact_on_modifier(handle: Unknown) -> Result<bool> {
+  let handle_val = utils::to_i64(handle)?;
+  Ok(interact::disable(handle_val))
+}
+
+/// Register a global hotkey such as `Ctrl+Shift+F9`, even while another application is focused
+/// `callback` receives the accelerator on every press; fails if another application holds the key
+#[napi(js_name = "registerHotkey")]
+pub fn register_hotkey(accelerator: String, callback: hotkey::HotkeyCallback) -> Result<()> {
+  hotkey::register(&accelerator, callback)
+}
+
+/// Unregister a global hotkey
+/// Returns `false` if the hotkey was not registered
+#[napi(js_name = "unregisterHotkey")]
+pub fn unregister_hotkey(accelerator: String) -> Result<bool> {
+  hotkey::unregister(&accelerator)
+}
+
 /// Get all visible windows
 #[napi(js_name = "getWindows")]
 pub fn get_windows() -> Result<Vec<WindowInfo>> {
@@ -135,6 +611,47 @@ pub fn set_window_opacity(handle: Unknown, opacity: f64) -> Result<()> {
   platform::set_window_opacity(handle_val, opacity)
 }
 
+/// Animate window opacity to `target` over `durationMs` in a native thread (default easing: linear)
+/// Resolves to `true` when done, or `false` if a newer fade on the same window cancelled it
+#[napi(js_name = "fadeWindowOpacity", ts_return_type = "Promise<boolean>")]
+pub fn fade_window_opacity<'env>(
+  env: &'env Env,
+  handle: Unknown,
+  target: f64,
+  duration_ms: u32,
+  easing: Option<Easing>,
+) -> Result<Object<'env>> {
+  let handle_val = utils::to_i64(handle)?;
+  let (deferred, promise) = env.create_deferred()?;
+
+  fade::start(
+    handle_val,
+    target.clamp(0.0, 1.0),
+    std::time::Duration::from_millis(duration_ms as u64),
+    easing.unwrap_or(Easing::Linear),
+    move |result| match result {
+      Ok(completed) => deferred.resolve(move |_| Ok(completed)),
+      Err(e) => deferred.reject(e),
+    },
+  )?;
+
+  Ok(promise)
+}
+
+/// Get window opacity (1.0 when it was never changed)
+#[napi(js_name = "getWindowOpacity")]
+pub fn get_window_opacity(handle: Unknown) -> Result<f64> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::get_window_opacity(handle_val)
+}
+
+/// Remove any opacity set on the window, restoring its untouched state
+#[napi(js_name = "removeWindowOpacity")]
+pub fn remove_window_opacity(handle: Unknown) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::remove_window_opacity(handle_val)
+}
+
 /// Get the executable path of the process that owns the window
 #[napi(js_name = "getWindowProcessPath")]
 pub fn get_window_process_path(handle: Unknown) -> Result<String> {
@@ -162,7 +679,15 @@ pub fn get_active_window() -> Result<Option<i64>> {
   platform::get_active_window()
 }
 
-/// Set the window state (Minimize, Maximize, Restore)
+/// Get the current window state (minimized, maximized, fullscreen, focused, always on top)
+#[napi(js_name = "getWindowState")]
+pub fn get_window_state(handle: Unknown) -> Result<Option<WindowStateInfo>> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::get_window_state(handle_val)
+}
+
+/// Set the window state (Minimize, Maximize, Restore, Fullscreen, ExitFullscreen, Hide, Show)
+/// Hide unmaps the window without destroying it, Show maps it again
 #[napi(js_name = "setWindowState")]
 pub fn set_window_state(handle: Unknown, state: WindowState) -> Result<()> {
   let handle_val = utils::to_i64(handle)?;
@@ -175,3 +700,459 @@ pub fn kill_window_process(handle: Unknown) -> Result<()> {
   let handle_val = utils::to_i64(handle)?;
   platform::kill_window_process(handle_val)
 }
+
+/// Get all connected monitors with their bounds, work areas and scale factors
+#[napi(js_name = "getMonitors")]
+pub fn get_monitors() -> Result<Vec<MonitorInfo>> {
+  platform::get_monitors()
+}
+
+/// Get the monitor that contains the largest part of the window
+#[napi(js_name = "getWindowMonitor")]
+pub fn get_window_monitor(handle: Unknown) -> Result<Option<MonitorInfo>> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::get_window_monitor(handle_val)
+}
+
+/// Get the window bounds without the cost of a full `getWindowInfo` lookup
+/// If `clientArea` is true, returns the client area instead of the outer frame
+#[napi(js_name = "getWindowBounds")]
+pub fn get_window_bounds(handle: Unknown, client_area: Option<bool>) -> Result<Option<Rect>> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::get_window_bounds(handle_val, client_area.unwrap_or(false))
+}
+
+/// Move and resize a window in a single atomic request
+#[napi(js_name = "setWindowBounds")]
+pub fn set_window_bounds(
+  handle: Unknown,
+  bounds: Rect,
+  options: Option<WindowBoundsOptions>,
+) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  let options = options.unwrap_or_default();
+  platform::set_window_bounds(
+    handle_val,
+    bounds,
+    options.client_area.unwrap_or(false),
+    options.animate.unwrap_or(false),
+  )
+}
+
+/// Get the window icon that best fits `preferredSize` pixels (default: the largest one)
+/// The smallest icon at least that large is picked, or the largest available
+#[napi(js_name = "getWindowIcon")]
+pub fn get_window_icon(handle: Unknown, preferred_size: Option<u32>) -> Result<Option<RgbaImage>> {
+  let handle_val = utils::to_i64(handle)?;
+  let mut icons = platform::get_window_icons(handle_val)?;
+
+  let size = |icon: &RgbaImage| icon.width.max(icon.height);
+  let largest = icons
+    .iter()
+    .enumerate()
+    .max_by_key(|(_, icon)| size(icon))
+    .map(|(index, _)| index);
+  let best = match preferred_size {
+    Some(preferred) => icons
+      .iter()
+      .enumerate()
+      .filter(|(_, icon)| size(icon) >= preferred)
+      .min_by_key(|(_, icon)| size(icon))
+      .map(|(index, _)| index)
+      .or(largest),
+    None => largest,
+  };
+
+  Ok(best.map(|index| icons.swap_remove(index)))
+}
+
+/// Get every icon size a window provides
+#[napi(js_name = "getWindowIcons")]
+pub fn get_window_icons(handle: Unknown) -> Result<Vec<RgbaImage>> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::get_window_icons(handle_val)
+}
+
+/// Set the window icon from one or more RGBA images of different sizes
+#[napi(js_name = "setWindowIcon")]
+pub fn set_window_icon(handle: Unknown, images: Vec<RgbaImage>) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+
+  if images.is_empty() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "At least one image is required",
+    ));
+  }
+  for image in &images {
+    let expected = image.width as usize * image.height as usize * 4;
+    if expected == 0 || image.data.len() != expected {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "Image data holds {} bytes, {}x{} RGBA needs {}",
+          image.data.len(),
+          image.width,
+          image.height,
+          expected
+        ),
+      ));
+    }
+  }
+
+  platform::set_window_icon(handle_val, &images)
+}
+
+/// Get the window title
+#[napi(js_name = "getWindowTitle")]
+pub fn get_window_title(handle: Unknown) -> Result<String> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::get_window_title(handle_val)
+}
+
+/// Set the window title
+#[napi(js_name = "setWindowTitle")]
+pub fn set_window_title(handle: Unknown, title: String) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::set_window_title(handle_val, &title)
+}
+
+/// Get all visible windows ordered from bottom to top (the last one is the topmost window)
+#[napi(js_name = "getWindowsInStackingOrder")]
+pub fn get_windows_in_stacking_order() -> Result<Vec<WindowInfo>> {
+  let mut windows = platform::get_windows()?;
+  windows.sort_by_key(|w| w.z_index);
+  Ok(windows)
+}
+
+/// Get the pointer position, the monitor under it and the held buttons and modifiers
+#[napi(js_name = "getCursorPosition")]
+pub fn get_cursor_position() -> Result<CursorPosition> {
+  let mut position = platform::get_cursor_position()?;
+  position.monitor = platform::get_monitors()?
+    .into_iter()
+    .find(|m| m.bounds.contains(position.x, position.y));
+  Ok(position)
+}
+
+/// Get the topmost window at a point in screen coordinates, ignoring `skipHandles`
+#[napi(js_name = "getWindowAtPoint")]
+pub fn get_window_at_point(
+  x: i32,
+  y: i32,
+  options: Option<WindowAtPointOptions>,
+) -> Result<Option<WindowInfo>> {
+  let skip = options.unwrap_or_default().skip_handles.unwrap_or_default();
+  let current_desktop = platform::get_current_desktop()?;
+
+  let mut windows = platform::get_windows()?;
+  windows.sort_by_key(|w| std::cmp::Reverse(w.z_index));
+  Ok(windows.into_iter().find(|w| {
+    let on_current_desktop = match current_desktop {
+      Some(current) => w.desktop < 0 || w.desktop as u32 == current,
+      None => true,
+    };
+    let bounds = Rect {
+      x: w.x,
+      y: w.y,
+      width: w.width,
+      height: w.height,
+    };
+
+    w.visible
+      && !w.minimized
+      && on_current_desktop
+      && !skip.contains(&w.handle)
+      && bounds.contains(x, y)
+  }))
+}
+
+/// Capture the pixels of a window, or of a region of it, as RGBA
+/// On X11 the part past the screen edges is cut off, so the image can be smaller than asked
+#[napi(js_name = "captureWindow")]
+pub fn capture_window(handle: Unknown, options: Option<CaptureOptions>) -> Result<RgbaImage> {
+  let handle_val = utils::to_i64(handle)?;
+  let options = options.unwrap_or_default();
+  platform::capture_window(
+    handle_val,
+    options.region,
+    options.include_frame.unwrap_or(false),
+  )
+}
+
+/// Stream frames of a window to `onFrame`, only when its content changed (XDamage on X11)
+/// Frames are dropped while JS is still busy with the previous one; call `stop()` on the result to end
+#[napi(js_name = "startWindowCapture")]
+pub fn start_window_capture(
+  handle: Unknown,
+  options: Option<WindowCaptureOptions>,
+  on_frame: capture::FrameCallback,
+) -> Result<capture::WindowCapture> {
+  let handle_val = utils::to_i64(handle)?;
+  let options = options.unwrap_or_default();
+  capture::start(
+    handle_val,
+    options.region,
+    options.include_frame.unwrap_or(false),
+    options.max_fps.unwrap_or(30.0),
+    on_frame,
+  )
+}
+
+/// Raise a window to the top of the stacking order
+#[napi(js_name = "raiseWindow")]
+pub fn raise_window(handle: Unknown) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::raise_window(handle_val)
+}
+
+/// Lower a window to the bottom of the stacking order
+#[napi(js_name = "lowerWindow")]
+pub fn lower_window(handle: Unknown) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::lower_window(handle_val)
+}
+
+/// Restack a window directly above a sibling window
+#[napi(js_name = "placeWindowAbove")]
+pub fn place_window_above(handle: Unknown, sibling: Unknown) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  let sibling_val = utils::to_i64(sibling)?;
+  platform::place_window_above(handle_val, sibling_val)
+}
+
+/// Get all virtual desktops (workspaces)
+#[napi(js_name = "getDesktops")]
+pub fn get_desktops() -> Result<Vec<DesktopInfo>> {
+  platform::get_desktops()
+}
+
+/// Get the index of the currently shown virtual desktop
+#[napi(js_name = "getCurrentDesktop")]
+pub fn get_current_desktop() -> Result<Option<u32>> {
+  platform::get_current_desktop()
+}
+
+/// Switch to another virtual desktop
+#[napi(js_name = "switchDesktop")]
+pub fn switch_desktop(index: u32) -> Result<()> {
+  platform::switch_desktop(index)
+}
+
+/// Move a window to another virtual desktop
+#[napi(js_name = "moveWindowToDesktop")]
+pub fn move_window_to_desktop(handle: Unknown, index: u32) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::move_window_to_desktop(handle_val, index)
+}
+
+/// Get the window manager flags currently set on a window
+#[napi(js_name = "getWindowFlags")]
+pub fn get_window_flags(handle: Unknown) -> Result<WindowFlags> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::get_window_flags(handle_val)
+}
+
+/// Add (`true`) or remove (`false`) window manager flags, omitted flags are left untouched
+#[napi(js_name = "setWindowFlags")]
+pub fn set_window_flags(handle: Unknown, flags: WindowFlags) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::set_window_flags(handle_val, &flags)
+}
+
+/// Toggle every flag set to `true`, returns the resulting flags
+#[napi(js_name = "toggleWindowFlags")]
+pub fn toggle_window_flags(handle: Unknown, flags: WindowFlags) -> Result<WindowFlags> {
+  let handle_val = utils::to_i64(handle)?;
+  let current = platform::get_window_flags(handle_val)?;
+
+  let toggle = |requested: Option<bool>, current: Option<bool>| {
+    if requested == Some(true) {
+      Some(!current.unwrap_or(false))
+    } else {
+      None
+    }
+  };
+
+  let changes = WindowFlags {
+    sticky: toggle(flags.sticky, current.sticky),
+    skip_taskbar: toggle(flags.skip_taskbar, current.skip_taskbar),
+    skip_pager: toggle(flags.skip_pager, current.skip_pager),
+    fullscreen: toggle(flags.fullscreen, current.fullscreen),
+    below: toggle(flags.below, current.below),
+    above: toggle(flags.above, current.above),
+    demands_attention: toggle(flags.demands_attention, current.demands_attention),
+  };
+  platform::set_window_flags(handle_val, &changes)?;
+
+  Ok(WindowFlags {
+    sticky: changes.sticky.or(current.sticky),
+    skip_taskbar: changes.skip_taskbar.or(current.skip_taskbar),
+    skip_pager: changes.skip_pager.or(current.skip_pager),
+    fullscreen: changes.fullscreen.or(current.fullscreen),
+    below: changes.below.or(current.below),
+    above: changes.above.or(current.above),
+    demands_attention: changes.demands_attention.or(current.demands_attention),
+  })
+}
+
+/// Set the window type hint (dock, notification, utility, splash, tooltip, desktop, normal...)
+/// Most window managers only honour a type change when the window is next mapped
+#[napi(js_name = "setWindowType")]
+pub fn set_window_type(handle: Unknown, window_type: WindowType) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::set_window_type(handle_val, window_type)
+}
+
+/// Remove (`false`) or restore (`true`) the title bar and borders of a window
+/// The original decorations are remembered so they can be restored later
+#[napi(js_name = "setDecorations")]
+pub fn set_decorations(handle: Unknown, enabled: bool) -> Result<()> {
+  let handle_val = utils::to_i64(handle)?;
+  platform::set_decorations(handle_val, enabled)
+}
+
+/// Keep an overlay window positioned and stacked just above a target window
+/// The overlay follows the target until `detachOverlay` is called or the target is destroyed
+#[napi(js_name = "attachOverlay")]
+pub fn attach_overlay(
+  overlay_handle: Unknown,
+  target_handle: Unknown,
+  options: Option<OverlayOptions>,
+) -> Result<()> {
+  let overlay_val = utils::to_i64(overlay_handle)?;
+  let target_val = utils::to_i64(target_handle)?;
+  let options = options.unwrap_or_default();
+  let offset = options.offset.unwrap_or_default();
+
+  overlay::attach(
+    overlay_val,
+    target_val,
+    overlay::Attachment {
+      anchor: options.anchor.unwrap_or(OverlayAnchor::TopLeft),
+      offset_x: offset.x,
+      offset_y: offset.y,
+      match_size: options.match_size.unwrap_or(false),
+      hide_when_target_minimized: options.hide_when_target_minimized.unwrap_or(true),
+      hide_when_target_unfocused: options.hide_when_target_unfocused.unwrap_or(false),
+    },
+  )
+}
+
+/// Stop an overlay from following its target, returns false if it was not attached
+#[napi(js_name = "detachOverlay")]
+pub fn detach_overlay(overlay_handle: Unknown) -> Result<bool> {
+  let overlay_val = utils::to_i64(overlay_handle)?;
+  Ok(overlay::detach(overlay_val))
+}
+
+/// Save bounds, state, desktop, opacity and on-top flag of the visible windows matching `query`
+/// Windows without a title, class or path cannot be matched again and are skipped
+#[napi(js_name = "captureLayout")]
+pub fn capture_layout(query: Option<WindowMatch>) -> Result<Layout> {
+  layout::capture(query.as_ref())
+}
+
+/// Reapply a layout saved by `captureLayout` to the current windows
+/// Each entry is matched by class and executable (the title breaking ties) to a different window
+#[napi(js_name = "restoreLayout")]
+pub fn restore_layout(layout: Layout) -> Result<RestoreLayoutResult> {
+  layout::restore(layout)
+}
+
+/// Apply `actions` to every window that starts matching `match`, as it appears or gets renamed
+/// Windows are watched natively while rules exist; returns the rule id
+#[napi(js_name = "addWindowRule")]
+pub fn add_window_rule(rule: WindowRuleOptions) -> Result<u32> {
+  rules::add(rule.window_match, rule.actions, rule.once.unwrap_or(false))
+}
+
+/// Remove a window rule, returns false if no rule has this id
+#[napi(js_name = "removeWindowRule")]
+pub fn remove_window_rule(id: u32) -> Result<bool> {
+  rules::remove(id)
+}
+
+/// List the registered window rules, in the order they are evaluated
+#[napi(js_name = "listWindowRules")]
+pub fn list_window_rules() -> Result<Vec<WindowRule>> {
+  rules::list()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+    Rect {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  fn parts(r: Rect) -> (i32, i32, i32, i32) {
+    (r.x, r.y, r.width, r.height)
+  }
+
+  #[test]
+  fn contains_includes_the_top_left_edges_only() {
+    let r = rect(10, 20, 30, 40);
+    assert!(r.contains(10, 20));
+    assert!(r.contains(39, 59));
+    assert!(!r.contains(40, 30));
+    assert!(!r.contains(20, 60));
+    assert!(!r.contains(9, 30));
+    assert!(!r.contains(20, 19));
+  }
+
+  #[test]
+  fn contains_nothing_when_empty() {
+    assert!(!rect(0, 0, 0, 0).contains(0, 0));
+    assert!(!rect(-5, -5, 10, 0).contains(0, -5));
+  }
+
+  #[test]
+  fn intersection_of_overlapping_rects() {
+    let a = rect(0, 0, 100, 50);
+    let b = rect(60, 20, 100, 100);
+    assert_eq!(a.intersection(&b).map(parts), Some((60, 20, 40, 30)));
+    assert_eq!(b.intersection(&a).map(parts), Some((60, 20, 40, 30)));
+  }
+
+  #[test]
+  fn intersection_with_a_contained_rect() {
+    let outer = rect(-100, -100, 300, 300);
+    let inner = rect(10, 10, 20, 20);
+    assert_eq!(
+      outer.intersection(&inner).map(parts),
+      Some((10, 10, 20, 20))
+    );
+  }
+
+  #[test]
+  fn intersection_of_touching_or_disjoint_rects() {
+    let a = rect(0, 0, 100, 100);
+    assert!(a.intersection(&rect(100, 0, 50, 50)).is_none());
+    assert!(a.intersection(&rect(0, 100, 50, 50)).is_none());
+    assert!(a.intersection(&rect(200, 200, 10, 10)).is_none());
+    assert!(a.intersection(&rect(10, 10, 0, 0)).is_none());
+  }
+
+  #[test]
+  fn clip_relative_keeps_the_part_inside_bounds() {
+    let screen = rect(0, 0, 1920, 1080);
+    // A window hanging off the left and bottom edges of the screen
+    let area = rect(0, 0, 800, 600);
+    assert_eq!(
+      area.clip_relative(-300, 700, &screen).map(parts),
+      Some((300, 0, 500, 380))
+    );
+    assert_eq!(
+      area.clip_relative(100, 100, &screen).map(parts),
+      Some((0, 0, 800, 600))
+    );
+    assert!(area.clip_relative(1920, 0, &screen).is_none());
+  }
+}
@@ -0,0 +1,167 @@
+use napi::bindgen_prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use crate::{platform, OverlayAnchor, Rect};
+
+lazy_static::lazy_static! {
+    /// Stop flag of the follower thread of each attached overlay
+    static ref ATTACHMENTS: Mutex<HashMap<i64, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+/// Resolved `attachOverlay` options
+#[derive(Clone, Copy)]
+pub struct Attachment {
+  pub anchor: OverlayAnchor,
+  pub offset_x: i32,
+  pub offset_y: i32,
+  pub match_size: bool,
+  pub hide_when_target_minimized: bool,
+  pub hide_when_target_unfocused: bool,
+}
+
+/// Fraction of the free space placed before the overlay, horizontally and vertically
+fn anchor_factors(anchor: OverlayAnchor) -> (i32, i32) {
+  match anchor {
+    OverlayAnchor::TopLeft => (0, 0),
+    OverlayAnchor::Top => (1, 0),
+    OverlayAnchor::TopRight => (2, 0),
+    OverlayAnchor::Left => (0, 1),
+    OverlayAnchor::Center => (1, 1),
+    OverlayAnchor::Right => (2, 1),
+    OverlayAnchor::BottomLeft => (0, 2),
+    OverlayAnchor::Bottom => (1, 2),
+    OverlayAnchor::BottomRight => (2, 2),
+  }
+}
+
+/// Bounds of the overlay so that its anchor point sits on the same anchor point of `target`
+fn overlay_bounds(target: Rect, width: i32, height: i32, attachment: &Attachment) -> Rect {
+  let (fx, fy) = anchor_factors(attachment.anchor);
+  Rect {
+    x: target.x + (target.width - width) * fx / 2 + attachment.offset_x,
+    y: target.y + (target.height - height) * fy / 2 + attachment.offset_y,
+    width,
+    height,
+  }
+}
+
+/// Bring the overlay in line with the target's current geometry and state
+/// `hidden` tracks whether the overlay is currently hidden by us
+fn follow(overlay: i64, target: i64, attachment: &Attachment, hidden: &mut bool) {
+  let Ok(Some(state)) = platform::get_window_state(target) else {
+    return;
+  };
+
+  // Interacting with the overlay itself does not count as leaving the target
+  let overlay_focused = platform::get_window_state(overlay)
+    .ok()
+    .flatten()
+    .is_some_and(|s| s.focused);
+  let hide = (attachment.hide_when_target_minimized && state.minimized)
+    || (attachment.hide_when_target_unfocused && !state.focused && !overlay_focused);
+
+  if hide != *hidden && platform::set_overlay_visible(overlay, !hide).is_ok() {
+    *hidden = hide;
+  }
+  if hide || state.minimized {
+    return;
+  }
+
+  let (Ok(Some(target_rect)), Ok(Some(current))) = (
+    platform::get_window_bounds(target, true),
+    platform::get_window_bounds(overlay, false),
+  ) else {
+    return;
+  };
+
+  let (width, height) = if attachment.match_size {
+    (target_rect.width, target_rect.height)
+  } else {
+    (current.width, current.height)
+  };
+  let bounds = overlay_bounds(target_rect, width, height, attachment);
+
+  // Only move when needed, our own moves would otherwise keep triggering updates
+  if (bounds.x, bounds.y, bounds.width, bounds.height)
+    != (current.x, current.y, current.width, current.height)
+  {
+    let _ = platform::set_window_bounds(overlay, bounds, false, false);
+  }
+  let _ = platform::place_window_above(overlay, target);
+}
+
+/// Keep `overlay` positioned and stacked above `target` until detached or the target is destroyed
+/// Attaching an overlay again replaces its previous attachment
+pub fn attach(overlay: i64, target: i64, attachment: Attachment) -> Result<()> {
+  if overlay == target {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "An overlay cannot be attached to itself",
+    ));
+  }
+
+  detach(overlay);
+
+  let stop = Arc::new(AtomicBool::new(false));
+  ATTACHMENTS
+    .lock()
+    .map_err(|_| Error::new(Status::GenericFailure, "Overlay state is poisoned"))?
+    .insert(overlay, stop.clone());
+
+  let (ready, started) = mpsc::channel();
+
+  let thread_stop = stop.clone();
+  thread::spawn(move || {
+    let mut hidden = false;
+    platform::watch_window_changes(target, &thread_stop, ready, || {
+      follow(overlay, target, &attachment, &mut hidden)
+    });
+
+    // Never leave the overlay hidden once it stops following
+    if hidden {
+      let _ = platform::set_overlay_visible(overlay, true);
+    }
+
+    forget(overlay, &thread_stop);
+  });
+
+  let result = started
+    .recv()
+    .map_err(|_| Error::new(Status::GenericFailure, "Overlay thread exited"))
+    .and_then(|r| r);
+  if result.is_err() {
+    forget(overlay, &stop);
+  }
+  result
+}
+
+/// Remove the attachment of `overlay` unless a newer one replaced it
+fn forget(overlay: i64, stop: &Arc<AtomicBool>) {
+  if let Ok(mut attachments) = ATTACHMENTS.lock() {
+    if attachments
+      .get(&overlay)
+      .is_some_and(|s| Arc::ptr_eq(s, stop))
+    {
+      attachments.remove(&overlay);
+    }
+  }
+}
+
+/// Stop following the target, returns false if the overlay was not attached
+pub fn detach(overlay: i64) -> bool {
+  let stop = ATTACHMENTS
+    .lock()
+    .ok()
+    .and_then(|mut attachments| attachments.remove(&overlay));
+
+  match stop {
+    Some(stop) => {
+      stop.store(true, Ordering::Relaxed);
+      true
+    }
+    None => false,
+  }
+}
@@ -1,24 +1,70 @@
-use crate::WindowInfo;
+use super::WindowState;
+use crate::hotkey::{self, HotkeyCommand};
+use crate::rules::WindowEvent;
+use crate::utils;
+use crate::{
+  CursorPosition, DesktopInfo, MonitorInfo, Rect, RgbaImage, WindowFlags, WindowInfo,
+  WindowStateInfo, WindowType,
+};
 use napi::bindgen_prelude::*;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::ffi::CStr;
-use std::os::raw::{c_char, c_int, c_long, c_ulong};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_long, c_uint, c_ulong};
 use std::ptr;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant};
 
+use x11::xfixes::{
+  XFixesCreateRegion, XFixesDestroyRegion, XFixesQueryExtension, XFixesSetWindowShapeRegion,
+};
 use x11::xlib::{
-  Display, IsViewable, PropModeReplace, Window, XChangeProperty, XCloseDisplay, XDefaultRootWindow,
-  XFetchName, XFlush, XFree, XGetWindowAttributes, XGetWindowProperty, XInternAtom, XOpenDisplay,
-  XWindowAttributes, XA_CARDINAL,
+  Above, Atom, Below, Button1Mask, Button2Mask, Button3Mask, ControlMask, Display, GrabModeAsync,
+  IsViewable, KeyPress, LSBFirst, LockMask, Mod1Mask, Mod2Mask, Mod4Mask, PropModeReplace,
+  ShiftMask, Window, XChangeProperty, XCloseDisplay, XConnectionNumber, XDefaultRootWindow,
+  XDestroyImage, XErrorEvent, XEvent, XFetchName, XFlush, XFree, XGetImage, XGetPixel,
+  XGetWindowAttributes, XGetWindowProperty, XGrabKey, XImage, XInternAtom, XKeysymToKeycode,
+  XNextEvent, XOpenDisplay, XPending, XQueryPointer, XQueryTree, XRectangle,
+  XResourceManagerString, XSetErrorHandler, XStringToKeysym, XSync, XTranslateCoordinates,
+  XUngrabKey, XWindowAttributes, ZPixmap, XA_ATOM, XA_CARDINAL, XA_STRING, XA_WINDOW,
+};
+use x11::xrandr::{
+  RR_DoubleScan, RR_Interlace, XRRFreeCrtcInfo, XRRFreeOutputInfo, XRRFreeScreenResources,
+  XRRGetCrtcInfo, XRRGetOutputInfo, XRRGetOutputPrimary, XRRGetScreenResourcesCurrent, XRRModeInfo,
+  XRRQueryExtension,
+};
+use x11::xshm::{
+  XShmAttach, XShmCreateImage, XShmDetach, XShmGetImage, XShmQueryExtension, XShmSegmentInfo,
 };
 
 lazy_static::lazy_static! {
     static ref CLICK_THROUGH_STATE: Mutex<HashMap<i64, bool>> = Mutex::new(HashMap::new());
+    /// Original _MOTIF_WM_HINTS (None when absent) of windows whose decorations were removed
+    static ref DECORATION_RESTORE: Mutex<HashMap<i64, Option<Vec<c_ulong>>>> = Mutex::new(HashMap::new());
+}
+
+/// Display connection owned by a polling thread, closed when the thread exits
+struct ThreadDisplay(*mut Display);
+
+impl Drop for ThreadDisplay {
+  fn drop(&mut self) {
+    unsafe {
+      XCloseDisplay(self.0);
+    }
+  }
+}
+
+thread_local! {
+    static THREAD_DISPLAY: RefCell<Option<ThreadDisplay>> = const { RefCell::new(None) };
 }
 
 /// Get X11 display connection
 fn get_display() -> Option<*mut Display> {
   unsafe {
+    install_x_error_handler();
+
     let display = XOpenDisplay(ptr::null());
     if display.is_null() {
       None
@@ -28,11 +74,205 @@ fn get_display() -> Option<*mut Display> {
   }
 }
 
+/// Run `f` on a connection kept open for the current thread
+/// For calls repeated in loops, such as overlay updates, which would otherwise reconnect every time
+fn with_thread_display<T>(f: impl FnOnce(*mut Display) -> Result<T>) -> Result<T> {
+  let display = THREAD_DISPLAY.with(|cell| -> Result<*mut Display> {
+    let mut cell = cell.borrow_mut();
+    if let Some(display) = cell.as_ref() {
+      return Ok(display.0);
+    }
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+    *cell = Some(ThreadDisplay(display));
+    Ok(display)
+  })?;
+  f(display)
+}
+
+/// Intern an atom from a null-terminated name
+fn intern_atom(display: *mut Display, name: &[u8]) -> Atom {
+  unsafe { XInternAtom(display, name.as_ptr() as *const c_char, 0) }
+}
+
+/// Serializes error trapping, the Xlib error handler being process-wide
+static X_ERROR_TRAP: Mutex<()> = Mutex::new(());
+/// Set by `x_error_trap` when the server reports an error
+static X_ERROR_SEEN: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "C" fn x_error_trap(_display: *mut Display, _event: *mut XErrorEvent) -> c_int {
+  X_ERROR_SEEN.store(true, Ordering::SeqCst);
+  0
+}
+
+/// Installed once by `install_x_error_handler`
+static X_ERROR_HANDLER: Once = Once::new();
+
+unsafe extern "C" fn x_error_ignore(_display: *mut Display, _event: *mut XErrorEvent) -> c_int {
+  0
+}
+
+/// Replace the default Xlib error handler, which exits the process on any error
+/// A window can vanish between two requests, calls on it then just fail or read nothing
+fn install_x_error_handler() {
+  X_ERROR_HANDLER.call_once(|| {
+    let _guard = X_ERROR_TRAP.lock().unwrap_or_else(|e| e.into_inner());
+    unsafe {
+      XSetErrorHandler(Some(x_error_ignore));
+    }
+  });
+}
+
+/// Run `f` with X errors recorded instead of ignored
+/// Returns the result of `f` and whether the server reported an error
+unsafe fn trap_x_errors<T>(display: *mut Display, f: impl FnOnce() -> T) -> (T, bool) {
+  let _guard = X_ERROR_TRAP.lock().unwrap_or_else(|e| e.into_inner());
+
+  X_ERROR_SEEN.store(false, Ordering::SeqCst);
+  let previous = XSetErrorHandler(Some(x_error_trap));
+  let value = f();
+  XSync(display, 0);
+  XSetErrorHandler(previous);
+
+  (value, X_ERROR_SEEN.load(Ordering::SeqCst))
+}
+
+/// Longest a watcher thread blocks on X events before checking its stop flag
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Block until the X connection or `wake` has data to read, or `timeout` elapses (None: no limit)
+/// Returns at once when Xlib already queued events, flushing pending requests on the way
+unsafe fn wait_for_x_events(display: *mut Display, wake: c_int, timeout: Option<Duration>) {
+  if XPending(display) > 0 {
+    return;
+  }
+
+  // Negative descriptors are ignored by poll
+  let mut fds = [
+    libc::pollfd {
+      fd: XConnectionNumber(display),
+      events: libc::POLLIN,
+      revents: 0,
+    },
+    libc::pollfd {
+      fd: wake,
+      events: libc::POLLIN,
+      revents: 0,
+    },
+  ];
+  // Round up so a deadline is not missed by the sub-millisecond rest
+  let timeout = timeout.map_or(-1, |t| {
+    t.as_micros().div_ceil(1000).min(c_int::MAX as u128) as c_int
+  });
+  libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout);
+}
+
+/// Read a format-32 property (CARDINAL, ATOM, WINDOW...) as a list of values
+fn get_property_longs(
+  display: *mut Display,
+  window: Window,
+  property: Atom,
+  req_type: Atom,
+) -> Vec<c_ulong> {
+  unsafe {
+    use std::slice;
+
+    let mut actual_type: c_ulong = 0;
+    let mut actual_format: c_int = 0;
+    let mut nitems: c_ulong = 0;
+    let mut bytes_after: c_ulong = 0;
+    let mut prop: *mut u8 = ptr::null_mut();
+
+    let status = XGetWindowProperty(
+      display,
+      window,
+      property,
+      0,
+      c_long::MAX,
+      0,
+      req_type,
+      &mut actual_type,
+      &mut actual_format,
+      &mut nitems,
+      &mut bytes_after,
+      &mut prop,
+    );
+
+    if status != 0 || prop.is_null() {
+      return vec![];
+    }
+
+    // Xlib hands back format-32 data as an array of C longs, whatever their size
+    let values = if actual_format == 32 && nitems > 0 {
+      slice::from_raw_parts(prop as *const c_ulong, nitems as usize).to_vec()
+    } else {
+      vec![]
+    };
+
+    XFree(prop as *mut _);
+    values
+  }
+}
+
 /// Enable or disable click-through on a window
 pub fn set_click_through(handle: i64, enable: bool) -> Result<()> {
+  // An empty input shape lets every click through, resetting it restores the default
+  if enable {
+    set_input_region(handle, Some(&[]))
+  } else {
+    set_input_region(handle, None)
+  }
+}
+
+/// Restrict the input shape of a window to a union of rectangles
+/// `Some(&[])` makes the window fully click-through, `None` fully interactive
+pub fn set_input_region(handle: i64, rects: Option<&[Rect]>) -> Result<()> {
+  // ShapeInput kind from the X Shape extension
+  const SHAPE_INPUT: c_int = 2;
+
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let mut event_base: c_int = 0;
+    let mut error_base: c_int = 0;
+    if XFixesQueryExtension(display, &mut event_base, &mut error_base) == 0 {
+      XCloseDisplay(display);
+      return Err(Error::new(
+        Status::GenericFailure,
+        "XFixes extension is not available",
+      ));
+    }
+
+    let window = handle as Window;
+    match rects {
+      Some(rects) => {
+        let mut xrects: Vec<XRectangle> = rects
+          .iter()
+          .filter(|r| r.width > 0 && r.height > 0)
+          .map(|r| XRectangle {
+            x: r.x.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+            y: r.y.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+            width: r.width.min(u16::MAX as i32) as u16,
+            height: r.height.min(u16::MAX as i32) as u16,
+          })
+          .collect();
+
+        let region = XFixesCreateRegion(display, xrects.as_mut_ptr(), xrects.len() as c_int);
+        XFixesSetWindowShapeRegion(display, window, SHAPE_INPUT, 0, 0, region);
+        XFixesDestroyRegion(display, region);
+      }
+      // Region 0 (None) resets the input shape to the whole window
+      None => XFixesSetWindowShapeRegion(display, window, SHAPE_INPUT, 0, 0, 0),
+    }
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+
   // Track state internally
   if let Ok(mut state) = CLICK_THROUGH_STATE.lock() {
-    state.insert(handle, enable);
+    state.insert(handle, rects.is_some_and(|r| r.is_empty()));
   }
   Ok(())
 }
@@ -53,8 +293,63 @@ pub fn is_click_through(handle: i64) -> Result<bool> {
   }
 }
 
+/// Read a format-8 text property as a string
+fn get_property_string(
+  display: *mut Display,
+  window: Window,
+  property: Atom,
+  req_type: Atom,
+) -> Option<String> {
+  unsafe {
+    use std::slice;
+
+    let mut actual_type: c_ulong = 0;
+    let mut actual_format: c_int = 0;
+    let mut nitems: c_ulong = 0;
+    let mut bytes_after: c_ulong = 0;
+    let mut prop: *mut u8 = ptr::null_mut();
+
+    let status = XGetWindowProperty(
+      display,
+      window,
+      property,
+      0,
+      c_long::MAX,
+      0,
+      req_type,
+      &mut actual_type,
+      &mut actual_format,
+      &mut nitems,
+      &mut bytes_after,
+      &mut prop,
+    );
+
+    if status != 0 || prop.is_null() {
+      return None;
+    }
+
+    let value = if actual_format == 8 && nitems > 0 {
+      let bytes = slice::from_raw_parts(prop, nitems as usize);
+      Some(String::from_utf8_lossy(bytes).into_owned())
+    } else {
+      None
+    };
+
+    XFree(prop as *mut _);
+    value
+  }
+}
+
 /// Get window name/title
 fn get_window_name(display: *mut Display, window: Window) -> String {
+  // Prefer the UTF-8 _NET_WM_NAME, WM_NAME is Latin-1 and mangles non-ASCII titles
+  let net_wm_name = intern_atom(display, b"_NET_WM_NAME\0");
+  let utf8_type = intern_atom(display, b"UTF8_STRING\0");
+
+  if let Some(title) = get_property_string(display, window, net_wm_name, utf8_type) {
+    return title;
+  }
+
   unsafe {
     let mut name: *mut c_char = ptr::null_mut();
     if XFetchName(display, window, &mut name as *mut *mut c_char) != 0 && !name.is_null() {
@@ -64,43 +359,7 @@ fn get_window_name(display: *mut Display, window: Window) -> String {
       XFree(name as *mut _);
       title
     } else {
-      // Try _NET_WM_NAME for UTF-8 names
-      let atom_name = b"_NET_WM_NAME\0";
-      let utf8_string = b"UTF8_STRING\0";
-
-      let net_wm_name = XInternAtom(display, atom_name.as_ptr() as *const c_char, 0);
-      let utf8_type = XInternAtom(display, utf8_string.as_ptr() as *const c_char, 0);
-
-      let mut actual_type: c_ulong = 0;
-      let mut actual_format: c_int = 0;
-      let mut nitems: c_ulong = 0;
-      let mut bytes_after: c_ulong = 0;
-      let mut prop: *mut u8 = ptr::null_mut();
-
-      let status = XGetWindowProperty(
-        display,
-        window,
-        net_wm_name,
-        0,
-        c_long::MAX,
-        0,
-        utf8_type,
-        &mut actual_type,
-        &mut actual_format,
-        &mut nitems,
-        &mut bytes_after,
-        &mut prop,
-      );
-
-      if status == 0 && !prop.is_null() && nitems > 0 {
-        let title = CStr::from_ptr(prop as *const c_char)
-          .to_string_lossy()
-          .into_owned();
-        XFree(prop as *mut _);
-        title
-      } else {
-        String::new()
-      }
+      String::new()
     }
   }
 }
@@ -142,6 +401,46 @@ fn get_window_pid(display: *mut Display, window: Window) -> u32 {
   }
 }
 
+/// Get the class part of WM_CLASS (instance and class are two NUL-terminated strings)
+fn get_window_class(display: *mut Display, window: Window) -> String {
+  get_property_string(
+    display,
+    window,
+    intern_atom(display, b"WM_CLASS\0"),
+    XA_STRING,
+  )
+  .and_then(|value| value.split('\0').nth(1).map(str::to_string))
+  .unwrap_or_default()
+}
+
+/// Resolve the executable of a process through /proc
+fn get_process_path(pid: u32) -> Option<String> {
+  if pid == 0 {
+    return None;
+  }
+  std::fs::read_link(format!("/proc/{}/exe", pid))
+    .ok()
+    .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Get the executable path of the process that owns the window (from _NET_WM_PID)
+pub fn get_window_process_path(handle: i64) -> Result<String> {
+  let pid = unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+    let pid = get_window_pid(display, handle as Window);
+    XCloseDisplay(display);
+    pid
+  };
+
+  get_process_path(pid).ok_or_else(|| {
+    Error::new(
+      Status::GenericFailure,
+      "Cannot resolve the process path of the window",
+    )
+  })
+}
+
 /// Get the _NET_CLIENT_LIST property to enumerate windows
 fn get_client_list(display: *mut Display, root: Window) -> Vec<Window> {
   unsafe {
@@ -190,6 +489,133 @@ fn get_client_list(display: *mut Display, root: Window) -> Vec<Window> {
   }
 }
 
+/// Get the _NET_CLIENT_LIST_STACKING property (bottom-to-top stacking order)
+fn get_client_list_stacking(display: *mut Display, root: Window) -> Vec<Window> {
+  get_property_longs(
+    display,
+    root,
+    intern_atom(display, b"_NET_CLIENT_LIST_STACKING\0"),
+    XA_WINDOW,
+  )
+}
+
+/// Position of a window in the stacking list, -1 when it is not listed
+fn stacking_index(stacking: &[Window], window: Window) -> i32 {
+  stacking
+    .iter()
+    .position(|&w| w == window)
+    .map(|i| i as i32)
+    .unwrap_or(-1)
+}
+
+/// Get the desktop of a window from _NET_WM_DESKTOP (-1 for all desktops or unknown)
+fn get_window_desktop(display: *mut Display, window: Window) -> i32 {
+  get_property_longs(
+    display,
+    window,
+    intern_atom(display, b"_NET_WM_DESKTOP\0"),
+    XA_CARDINAL,
+  )
+  .first()
+  .map(|&desktop| desktop as u32 as i32)
+  .unwrap_or(-1)
+}
+
+/// Get the window that currently has the focus from _NET_ACTIVE_WINDOW
+fn get_active_client(display: *mut Display, root: Window) -> Window {
+  get_property_longs(
+    display,
+    root,
+    intern_atom(display, b"_NET_ACTIVE_WINDOW\0"),
+    XA_WINDOW,
+  )
+  .first()
+  .copied()
+  .unwrap_or(0)
+}
+
+/// ICCCM WithdrawnState, the window is not shown by the window manager
+const WITHDRAWN_STATE: c_int = 0;
+/// ICCCM IconicState, the window is minimized
+const ICONIC_STATE: c_int = 3;
+
+/// Get the ICCCM WM_STATE of a window, None when the window manager did not set one
+fn get_icccm_state(display: *mut Display, window: Window) -> Option<c_int> {
+  let wm_state = intern_atom(display, b"WM_STATE\0");
+  get_property_longs(display, window, wm_state, wm_state)
+    .first()
+    .map(|&value| value as c_int)
+}
+
+/// Whether the window manager shows the window, minimized windows included
+/// Iconic windows are unmapped, so the map state is only used without WM_STATE
+fn is_window_shown(display: *mut Display, window: Window, attrs: &XWindowAttributes) -> bool {
+  match get_icccm_state(display, window) {
+    Some(state) => state != WITHDRAWN_STATE,
+    None => attrs.map_state == IsViewable,
+  }
+}
+
+/// Read the window state from WM_STATE and _NET_WM_STATE
+fn read_window_state(display: *mut Display, window: Window, active: Window) -> WindowStateInfo {
+  let state = get_wm_state(display, window);
+  let has = |name: &[u8]| state.contains(&intern_atom(display, name));
+
+  let iconic = get_icccm_state(display, window) == Some(ICONIC_STATE);
+
+  WindowStateInfo {
+    minimized: iconic || has(b"_NET_WM_STATE_HIDDEN\0"),
+    maximized: has(b"_NET_WM_STATE_MAXIMIZED_VERT\0") && has(b"_NET_WM_STATE_MAXIMIZED_HORZ\0"),
+    fullscreen: has(b"_NET_WM_STATE_FULLSCREEN\0"),
+    focused: window == active || has(b"_NET_WM_STATE_FOCUSED\0"),
+    always_on_top: has(b"_NET_WM_STATE_ABOVE\0"),
+  }
+}
+
+/// _NET_WM_WINDOW_TYPE atom names for each window type
+const WINDOW_TYPE_ATOMS: [(WindowType, &[u8]); 14] = [
+  (WindowType::Normal, b"_NET_WM_WINDOW_TYPE_NORMAL\0"),
+  (WindowType::Desktop, b"_NET_WM_WINDOW_TYPE_DESKTOP\0"),
+  (WindowType::Dock, b"_NET_WM_WINDOW_TYPE_DOCK\0"),
+  (WindowType::Toolbar, b"_NET_WM_WINDOW_TYPE_TOOLBAR\0"),
+  (WindowType::Menu, b"_NET_WM_WINDOW_TYPE_MENU\0"),
+  (WindowType::Utility, b"_NET_WM_WINDOW_TYPE_UTILITY\0"),
+  (WindowType::Splash, b"_NET_WM_WINDOW_TYPE_SPLASH\0"),
+  (WindowType::Dialog, b"_NET_WM_WINDOW_TYPE_DIALOG\0"),
+  (
+    WindowType::DropdownMenu,
+    b"_NET_WM_WINDOW_TYPE_DROPDOWN_MENU\0",
+  ),
+  (WindowType::PopupMenu, b"_NET_WM_WINDOW_TYPE_POPUP_MENU\0"),
+  (WindowType::Tooltip, b"_NET_WM_WINDOW_TYPE_TOOLTIP\0"),
+  (
+    WindowType::Notification,
+    b"_NET_WM_WINDOW_TYPE_NOTIFICATION\0",
+  ),
+  (WindowType::Combo, b"_NET_WM_WINDOW_TYPE_COMBO\0"),
+  (WindowType::Dnd, b"_NET_WM_WINDOW_TYPE_DND\0"),
+];
+
+/// Get the window type from _NET_WM_WINDOW_TYPE (the first recognised entry wins)
+fn get_window_type(display: *mut Display, window: Window) -> WindowType {
+  let types = get_property_longs(
+    display,
+    window,
+    intern_atom(display, b"_NET_WM_WINDOW_TYPE\0"),
+    XA_ATOM,
+  );
+
+  types
+    .iter()
+    .find_map(|&atom| {
+      WINDOW_TYPE_ATOMS
+        .iter()
+        .find(|(_, name)| intern_atom(display, name) == atom)
+        .map(|(window_type, _)| *window_type)
+    })
+    .unwrap_or(WindowType::Normal)
+}
+
 /// Get all visible windows
 pub fn get_windows() -> Result<Vec<WindowInfo>> {
   unsafe {
@@ -198,6 +624,8 @@ pub fn get_windows() -> Result<Vec<WindowInfo>> {
 
     let root = XDefaultRootWindow(display);
     let windows = get_client_list(display, root);
+    let stacking = get_client_list_stacking(display, root);
+    let active = get_active_client(display, root);
 
     let mut result = Vec::new();
 
@@ -217,22 +645,39 @@ pub fn get_windows() -> Result<Vec<WindowInfo>> {
         continue;
       }
 
-      // Skip unmapped (invisible) windows
-      if attrs.map_state != IsViewable {
+      // Skip hidden windows, minimized ones are unmapped but still listed
+      if !is_window_shown(display, window, &attrs) {
         continue;
       }
 
+      let state = read_window_state(display, window, active);
+      // Attributes are relative to the frame, report root coordinates instead
+      let bounds = get_client_rect(display, window).unwrap_or(Rect {
+        x: attrs.x,
+        y: attrs.y,
+        width: attrs.width,
+        height: attrs.height,
+      });
+
       result.push(WindowInfo {
         handle: window as i64,
         title,
         process_id,
-        class_name: String::new(),
+        class_name: get_window_class(display, window),
         visible: true,
-        x: attrs.x,
-        y: attrs.y,
-        width: attrs.width,
-        height: attrs.height,
-        path: String::new(),
+        x: bounds.x,
+        y: bounds.y,
+        width: bounds.width,
+        height: bounds.height,
+        path: get_process_path(process_id).unwrap_or_default(),
+        z_index: stacking_index(&stacking, window),
+        desktop: get_window_desktop(display, window),
+        minimized: state.minimized,
+        maximized: state.maximized,
+        fullscreen: state.fullscreen,
+        focused: state.focused,
+        always_on_top: state.always_on_top,
+        window_type: get_window_type(display, window),
       });
     }
 
@@ -253,23 +698,41 @@ pub fn get_window_info(handle: i64) -> Result<Option<WindowInfo>> {
 
     let mut attrs: XWindowAttributes = std::mem::zeroed();
     let visible = if XGetWindowAttributes(display, window, &mut attrs) != 0 {
-      attrs.map_state == IsViewable
+      is_window_shown(display, window, &attrs)
     } else {
       XCloseDisplay(display);
       return Ok(None);
     };
 
+    let root = XDefaultRootWindow(display);
+    let stacking = get_client_list_stacking(display, root);
+    let state = read_window_state(display, window, get_active_client(display, root));
+    let bounds = get_client_rect(display, window).unwrap_or(Rect {
+      x: attrs.x,
+      y: attrs.y,
+      width: attrs.width,
+      height: attrs.height,
+    });
+
     let info = WindowInfo {
       handle,
       title,
       process_id,
-      class_name: String::new(),
+      class_name: get_window_class(display, window),
       visible,
-      x: attrs.x,
-      y: attrs.y,
-      width: attrs.width,
-      height: attrs.height,
-      path: String::new(),
+      x: bounds.x,
+      y: bounds.y,
+      width: bounds.width,
+      height: bounds.height,
+      path: get_process_path(process_id).unwrap_or_default(),
+      z_index: stacking_index(&stacking, window),
+      desktop: get_window_desktop(display, window),
+      minimized: state.minimized,
+      maximized: state.maximized,
+      fullscreen: state.fullscreen,
+      focused: state.focused,
+      always_on_top: state.always_on_top,
+      window_type: get_window_type(display, window),
     };
 
     XCloseDisplay(display);
@@ -277,36 +740,28 @@ pub fn get_window_info(handle: i64) -> Result<Option<WindowInfo>> {
   }
 }
 
-/// Set window always on top using _NET_WM_STATE
-pub fn set_always_on_top(handle: i64, on_top: bool) -> Result<()> {
+/// Send an EWMH client message about `window` to the root window
+fn send_client_message(
+  display: *mut Display,
+  window: Window,
+  message_type: Atom,
+  data: [c_long; 5],
+) {
   unsafe {
     use x11::xlib::{
       ClientMessage, SubstructureNotifyMask, SubstructureRedirectMask, XEvent, XSendEvent,
     };
 
-    let display =
-      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
-
-    let window = handle as Window;
     let root = XDefaultRootWindow(display);
 
-    let net_wm_state = b"_NET_WM_STATE\0";
-    let net_wm_state_above = b"_NET_WM_STATE_ABOVE\0";
-
-    let wm_state = XInternAtom(display, net_wm_state.as_ptr() as *const c_char, 0);
-    let state_above = XInternAtom(display, net_wm_state_above.as_ptr() as *const c_char, 0);
-
     let mut event: XEvent = std::mem::zeroed();
     event.client_message.type_ = ClientMessage;
     event.client_message.window = window;
-    event.client_message.message_type = wm_state;
+    event.client_message.message_type = message_type;
     event.client_message.format = 32;
-    event
-      .client_message
-      .data
-      .set_long(0, if on_top { 1 } else { 0 });
-    event.client_message.data.set_long(1, state_above as c_long);
-    event.client_message.data.set_long(2, 0);
+    for (i, value) in data.iter().enumerate() {
+      event.client_message.data.set_long(i, *value);
+    }
 
     XSendEvent(
       display,
@@ -315,6 +770,37 @@ pub fn set_always_on_top(handle: i64, on_top: bool) -> Result<()> {
       SubstructureRedirectMask | SubstructureNotifyMask,
       &mut event,
     );
+  }
+}
+
+/// Check whether the window manager advertises an atom in _NET_SUPPORTED
+fn wm_supports(display: *mut Display, root: Window, atom: Atom) -> bool {
+  get_property_longs(
+    display,
+    root,
+    intern_atom(display, b"_NET_SUPPORTED\0"),
+    XA_ATOM,
+  )
+  .contains(&atom)
+}
+
+/// Set window always on top using _NET_WM_STATE
+pub fn set_always_on_top(handle: i64, on_top: bool) -> Result<()> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let window = handle as Window;
+
+    let wm_state = intern_atom(display, b"_NET_WM_STATE\0");
+    let state_above = intern_atom(display, b"_NET_WM_STATE_ABOVE\0");
+
+    send_client_message(
+      display,
+      window,
+      wm_state,
+      [if on_top { 1 } else { 0 }, state_above as c_long, 0, 0, 0],
+    );
 
     XFlush(display);
     XCloseDisplay(display);
@@ -333,8 +819,9 @@ pub fn set_window_opacity(handle: i64, opacity: f64) -> Result<()> {
     let atom_name = b"_NET_WM_WINDOW_OPACITY\0";
     let opacity_atom = XInternAtom(display, atom_name.as_ptr() as *const c_char, 0);
 
-    // Opacity is stored as unsigned 32-bit value where 0xFFFFFFFF = fully opaque
-    let opacity_value = (opacity * 0xFFFFFFFF_u32 as f64) as u32;
+    // Opacity is stored as unsigned 32-bit value where 0xFFFFFFFF = fully opaque,
+    // handed to Xlib as a C long like all format-32 data
+    let opacity_value = (opacity * 0xFFFFFFFF_u32 as f64) as u32 as c_ulong;
 
     XChangeProperty(
       display,
@@ -343,7 +830,7 @@ pub fn set_window_opacity(handle: i64, opacity: f64) -> Result<()> {
       XA_CARDINAL,
       32,
       PropModeReplace,
-      &opacity_value as *const u32 as *const u8,
+      &opacity_value as *const c_ulong as *const u8,
       1,
     );
 
@@ -352,3 +839,1909 @@ pub fn set_window_opacity(handle: i64, opacity: f64) -> Result<()> {
   }
   Ok(())
 }
+
+/// Get window opacity from _NET_WM_WINDOW_OPACITY (1.0 when the property is absent)
+pub fn get_window_opacity(handle: i64) -> Result<f64> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let values = get_property_longs(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_WM_WINDOW_OPACITY\0"),
+      XA_CARDINAL,
+    );
+
+    XCloseDisplay(display);
+    Ok(
+      values
+        .first()
+        .map_or(1.0, |&v| v as u32 as f64 / 0xFFFFFFFF_u32 as f64),
+    )
+  }
+}
+
+/// Delete _NET_WM_WINDOW_OPACITY, leaving opacity to the compositor defaults
+pub fn remove_window_opacity(handle: i64) -> Result<()> {
+  unsafe {
+    use x11::xlib::XDeleteProperty;
+
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    XDeleteProperty(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_WM_WINDOW_OPACITY\0"),
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Compute the refresh rate of an XRandR mode in Hz
+fn mode_refresh_rate(mode: &XRRModeInfo) -> f64 {
+  if mode.hTotal == 0 || mode.vTotal == 0 {
+    return 0.0;
+  }
+
+  let mut v_total = mode.vTotal as f64;
+  if mode.modeFlags & RR_DoubleScan as c_ulong != 0 {
+    v_total *= 2.0;
+  }
+  if mode.modeFlags & RR_Interlace as c_ulong != 0 {
+    v_total /= 2.0;
+  }
+
+  mode.dotClock as f64 / (mode.hTotal as f64 * v_total)
+}
+
+/// Derive the desktop scale factor from the Xft.dpi resource (X11 has no per-output scaling)
+fn get_scale_factor(display: *mut Display) -> f64 {
+  unsafe {
+    let resources = XResourceManagerString(display);
+    if resources.is_null() {
+      return 1.0;
+    }
+
+    CStr::from_ptr(resources)
+      .to_string_lossy()
+      .lines()
+      .find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim() == "Xft.dpi" {
+          value.trim().parse::<f64>().ok()
+        } else {
+          None
+        }
+      })
+      .filter(|dpi| *dpi > 0.0)
+      .map(|dpi| dpi / 96.0)
+      .unwrap_or(1.0)
+  }
+}
+
+/// Get the work area of the current desktop from _NET_WORKAREA
+fn get_work_area(display: *mut Display, root: Window) -> Option<Rect> {
+  let workarea = get_property_longs(
+    display,
+    root,
+    intern_atom(display, b"_NET_WORKAREA\0"),
+    XA_CARDINAL,
+  );
+  let current = get_property_longs(
+    display,
+    root,
+    intern_atom(display, b"_NET_CURRENT_DESKTOP\0"),
+    XA_CARDINAL,
+  )
+  .first()
+  .copied()
+  .unwrap_or(0) as usize;
+
+  // One x, y, width, height quadruple per desktop
+  let area = workarea
+    .chunks_exact(4)
+    .nth(current)
+    .or_else(|| workarea.chunks_exact(4).next())?;
+
+  Some(Rect {
+    x: area[0] as c_long as i32,
+    y: area[1] as c_long as i32,
+    width: area[2] as i32,
+    height: area[3] as i32,
+  })
+}
+
+/// Enumerate active CRTCs through XRandR
+fn get_randr_monitors(display: *mut Display, root: Window) -> Vec<MonitorInfo> {
+  unsafe {
+    use std::slice;
+
+    let mut event_base: c_int = 0;
+    let mut error_base: c_int = 0;
+    if XRRQueryExtension(display, &mut event_base, &mut error_base) == 0 {
+      return vec![];
+    }
+
+    let resources = XRRGetScreenResourcesCurrent(display, root);
+    if resources.is_null() {
+      return vec![];
+    }
+
+    let primary_output = XRRGetOutputPrimary(display, root);
+    let work_area = get_work_area(display, root);
+    let scale_factor = get_scale_factor(display);
+
+    let res = &*resources;
+    let crtcs = slice::from_raw_parts(res.crtcs, res.ncrtc.max(0) as usize);
+    let modes = slice::from_raw_parts(res.modes, res.nmode.max(0) as usize);
+
+    let mut monitors = Vec::new();
+
+    for &crtc in crtcs {
+      let crtc_info = XRRGetCrtcInfo(display, resources, crtc);
+      if crtc_info.is_null() {
+        continue;
+      }
+
+      let info = &*crtc_info;
+      // Disabled CRTCs have no mode and no outputs attached
+      if info.mode == 0 || info.noutput <= 0 {
+        XRRFreeCrtcInfo(crtc_info);
+        continue;
+      }
+
+      let outputs = slice::from_raw_parts(info.outputs, info.noutput as usize);
+
+      let output_info = XRRGetOutputInfo(display, resources, outputs[0]);
+      let name = if output_info.is_null() {
+        String::new()
+      } else {
+        let out = &*output_info;
+        let bytes = slice::from_raw_parts(out.name as *const u8, out.nameLen.max(0) as usize);
+        let name = String::from_utf8_lossy(bytes).into_owned();
+        XRRFreeOutputInfo(output_info);
+        name
+      };
+
+      let bounds = Rect {
+        x: info.x,
+        y: info.y,
+        width: info.width as i32,
+        height: info.height as i32,
+      };
+
+      let refresh_rate = modes
+        .iter()
+        .find(|m| m.id == info.mode)
+        .map(mode_refresh_rate)
+        .unwrap_or(0.0);
+
+      monitors.push(MonitorInfo {
+        name,
+        bounds,
+        work_area: work_area
+          .and_then(|area| area.intersection(&bounds))
+          .unwrap_or(bounds),
+        primary: outputs.contains(&primary_output),
+        refresh_rate,
+        scale_factor,
+      });
+
+      XRRFreeCrtcInfo(crtc_info);
+    }
+
+    XRRFreeScreenResources(resources);
+    monitors
+  }
+}
+
+/// Get all connected monitors
+pub fn get_monitors() -> Result<Vec<MonitorInfo>> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let root = XDefaultRootWindow(display);
+    let mut monitors = get_randr_monitors(display, root);
+
+    // Without XRandR, report the whole root window as a single monitor
+    if monitors.is_empty() {
+      let mut attrs: XWindowAttributes = std::mem::zeroed();
+      if XGetWindowAttributes(display, root, &mut attrs) != 0 {
+        let bounds = Rect {
+          x: 0,
+          y: 0,
+          width: attrs.width,
+          height: attrs.height,
+        };
+        monitors.push(MonitorInfo {
+          name: String::from("default"),
+          bounds,
+          work_area: get_work_area(display, root)
+            .and_then(|area| area.intersection(&bounds))
+            .unwrap_or(bounds),
+          primary: true,
+          refresh_rate: 0.0,
+          scale_factor: get_scale_factor(display),
+        });
+      }
+    }
+
+    // Flag the first monitor as primary when the server reports none
+    if !monitors.is_empty() && !monitors.iter().any(|m| m.primary) {
+      monitors[0].primary = true;
+    }
+
+    XCloseDisplay(display);
+    Ok(monitors)
+  }
+}
+
+/// Get the monitor that contains the largest part of the window
+pub fn get_window_monitor(handle: i64) -> Result<Option<MonitorInfo>> {
+  let bounds = unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let bounds = get_client_rect(display, handle as Window);
+    XCloseDisplay(display);
+
+    match bounds {
+      Some(bounds) => bounds,
+      None => return Ok(None),
+    }
+  };
+
+  let monitors = get_monitors()?;
+  let best = monitors
+    .iter()
+    .filter_map(|m| {
+      m.bounds
+        .intersection(&bounds)
+        .map(|area| (m, area.width as i64 * area.height as i64))
+    })
+    .max_by_key(|(_, area)| *area)
+    .map(|(m, _)| m.clone());
+
+  Ok(best.or_else(|| monitors.iter().find(|m| m.primary).cloned()))
+}
+
+/// Read _NET_FRAME_EXTENTS as (left, right, top, bottom); zero for undecorated windows
+fn get_frame_extents(display: *mut Display, window: Window) -> (i32, i32, i32, i32) {
+  let extents = get_property_longs(
+    display,
+    window,
+    intern_atom(display, b"_NET_FRAME_EXTENTS\0"),
+    XA_CARDINAL,
+  );
+
+  if extents.len() >= 4 {
+    (
+      extents[0] as i32,
+      extents[1] as i32,
+      extents[2] as i32,
+      extents[3] as i32,
+    )
+  } else {
+    (0, 0, 0, 0)
+  }
+}
+
+/// Get the client area of a window in root coordinates
+fn get_client_rect(display: *mut Display, window: Window) -> Option<Rect> {
+  unsafe {
+    let mut attrs: XWindowAttributes = std::mem::zeroed();
+    if XGetWindowAttributes(display, window, &mut attrs) == 0 {
+      return None;
+    }
+
+    // Window attributes are relative to the parent (usually the WM frame)
+    let mut x: c_int = 0;
+    let mut y: c_int = 0;
+    let mut child: Window = 0;
+    XTranslateCoordinates(
+      display,
+      window,
+      XDefaultRootWindow(display),
+      0,
+      0,
+      &mut x,
+      &mut y,
+      &mut child,
+    );
+
+    Some(Rect {
+      x,
+      y,
+      width: attrs.width,
+      height: attrs.height,
+    })
+  }
+}
+
+/// Get window bounds, either the client area or the outer frame including decorations
+pub fn get_window_bounds(handle: i64, client_area: bool) -> Result<Option<Rect>> {
+  with_thread_display(|display| {
+    let window = handle as Window;
+    let bounds = get_client_rect(display, window).map(|client| {
+      if client_area {
+        client
+      } else {
+        let (left, right, top, bottom) = get_frame_extents(display, window);
+        Rect {
+          x: client.x - left,
+          y: client.y - top,
+          width: client.width + left + right,
+          height: client.height + top + bottom,
+        }
+      }
+    });
+
+    Ok(bounds)
+  })
+}
+
+/// Move and resize a window in a single request
+pub fn set_window_bounds(
+  handle: i64,
+  bounds: Rect,
+  client_area: bool,
+  _animate: bool,
+) -> Result<()> {
+  use x11::xlib::{NorthWestGravity, StaticGravity, XMoveResizeWindow};
+
+  with_thread_display(|display| unsafe {
+    let window = handle as Window;
+    let root = XDefaultRootWindow(display);
+
+    // Sizes are always requested for the client area
+    let (left, right, top, bottom) = get_frame_extents(display, window);
+    let (width, height) = if client_area {
+      (bounds.width, bounds.height)
+    } else {
+      (bounds.width - left - right, bounds.height - top - bottom)
+    };
+
+    if width <= 0 || height <= 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Window bounds leave no room for the client area",
+      ));
+    }
+
+    let moveresize = intern_atom(display, b"_NET_MOVERESIZE_WINDOW\0");
+    if wm_supports(display, root, moveresize) {
+      // StaticGravity positions the client area, NorthWestGravity the frame
+      let gravity = if client_area {
+        StaticGravity
+      } else {
+        NorthWestGravity
+      };
+      // Bits 8-11: x, y, width and height present; bits 12-13: source is a pager/tool
+      let flags = gravity as c_long | (0b1111 << 8) | (2 << 12);
+
+      send_client_message(
+        display,
+        window,
+        moveresize,
+        [
+          flags,
+          bounds.x as c_long,
+          bounds.y as c_long,
+          width as c_long,
+          height as c_long,
+        ],
+      );
+    } else {
+      // Without an EWMH window manager the window is positioned by its client area
+      let (x, y) = if client_area {
+        (bounds.x, bounds.y)
+      } else {
+        (bounds.x + left, bounds.y + top)
+      };
+      XMoveResizeWindow(display, window, x, y, width as u32, height as u32);
+    }
+
+    XFlush(display);
+    Ok(())
+  })
+}
+
+/// Get the window title
+pub fn get_window_title(handle: i64) -> Result<String> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let title = get_window_name(display, handle as Window);
+
+    XCloseDisplay(display);
+    Ok(title)
+  }
+}
+
+/// Set the window title, writing both WM_NAME and the UTF-8 _NET_WM_NAME
+pub fn set_window_title(handle: i64, title: &str) -> Result<()> {
+  unsafe {
+    use x11::xlib::XStoreName;
+
+    // WM_NAME is Latin-1, replace anything outside of it
+    let latin1: Vec<u8> = title
+      .chars()
+      .map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' })
+      .collect();
+    let latin1 = CString::new(latin1)
+      .map_err(|_| Error::new(Status::InvalidArg, "Title must not contain NUL characters"))?;
+
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let window = handle as Window;
+
+    XStoreName(display, window, latin1.as_ptr());
+
+    XChangeProperty(
+      display,
+      window,
+      intern_atom(display, b"_NET_WM_NAME\0"),
+      intern_atom(display, b"UTF8_STRING\0"),
+      8,
+      PropModeReplace,
+      title.as_ptr(),
+      title.len() as c_int,
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Restack a window relative to a sibling (or the whole stack when `sibling` is 0)
+fn restack_window(handle: i64, sibling: Window, detail: c_int) -> Result<()> {
+  use x11::xlib::{
+    CWSibling, CWStackMode, XConfigureWindow, XLowerWindow, XRaiseWindow, XWindowChanges,
+  };
+
+  with_thread_display(|display| unsafe {
+    let window = handle as Window;
+    let root = XDefaultRootWindow(display);
+
+    let restack = intern_atom(display, b"_NET_RESTACK_WINDOW\0");
+    if wm_supports(display, root, restack) {
+      // Source indication 2: request comes from a pager/tool
+      send_client_message(
+        display,
+        window,
+        restack,
+        [2, sibling as c_long, detail as c_long, 0, 0],
+      );
+    } else if sibling != 0 {
+      let mut changes: XWindowChanges = std::mem::zeroed();
+      changes.sibling = sibling;
+      changes.stack_mode = detail;
+      XConfigureWindow(
+        display,
+        window,
+        (CWSibling | CWStackMode) as u32,
+        &mut changes,
+      );
+    } else if detail == Above {
+      XRaiseWindow(display, window);
+    } else {
+      XLowerWindow(display, window);
+    }
+
+    XFlush(display);
+    Ok(())
+  })
+}
+
+/// Raise a window to the top of the stacking order
+pub fn raise_window(handle: i64) -> Result<()> {
+  restack_window(handle, 0, Above)
+}
+
+/// Lower a window to the bottom of the stacking order
+pub fn lower_window(handle: i64) -> Result<()> {
+  restack_window(handle, 0, Below)
+}
+
+/// Restack a window directly above a sibling window
+pub fn place_window_above(handle: i64, sibling: i64) -> Result<()> {
+  // Skip the request when already in place, every restack is reported to watchers again
+  let in_place = with_thread_display(|display| {
+    let stacking = get_client_list_stacking(display, unsafe { XDefaultRootWindow(display) });
+    let index = stacking_index(&stacking, sibling as Window);
+    Ok(index >= 0 && stacking.get(index as usize + 1) == Some(&(handle as Window)))
+  })?;
+  if in_place {
+    return Ok(());
+  }
+  restack_window(handle, sibling as Window, Above)
+}
+
+/// Get the window that has the focus, from _NET_ACTIVE_WINDOW
+pub fn get_active_window() -> Result<Option<i64>> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let active = get_active_client(display, XDefaultRootWindow(display));
+
+    XCloseDisplay(display);
+    Ok((active != 0).then_some(active as i64))
+  }
+}
+
+/// Ask the window manager to activate a window through _NET_ACTIVE_WINDOW
+pub fn focus_window(handle: i64) -> Result<()> {
+  unsafe {
+    use x11::xlib::CurrentTime;
+
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    // Source indication 2: request comes from a pager/tool
+    send_client_message(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_ACTIVE_WINDOW\0"),
+      [2, CurrentTime as c_long, 0, 0, 0],
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Ask the window manager to close a window through _NET_CLOSE_WINDOW
+pub fn close_window(handle: i64) -> Result<()> {
+  unsafe {
+    use x11::xlib::CurrentTime;
+
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    send_client_message(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_CLOSE_WINDOW\0"),
+      [CurrentTime as c_long, 2, 0, 0, 0],
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Kill the process owning a window, found through _NET_WM_PID
+pub fn kill_window_process(handle: i64) -> Result<()> {
+  let process_id = unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+    let process_id = get_window_pid(display, handle as Window);
+    XCloseDisplay(display);
+    process_id
+  };
+
+  if process_id == 0 {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Window does not report its process (_NET_WM_PID)",
+    ));
+  }
+
+  if unsafe { libc::kill(process_id as libc::pid_t, libc::SIGKILL) } != 0 {
+    return Err(Error::new(
+      Status::GenericFailure,
+      format!(
+        "Failed to terminate process: {}",
+        std::io::Error::last_os_error()
+      ),
+    ));
+  }
+  Ok(())
+}
+
+/// Read a single CARDINAL from the root window
+fn get_root_cardinal(display: *mut Display, root: Window, name: &[u8]) -> Option<u32> {
+  get_property_longs(display, root, intern_atom(display, name), XA_CARDINAL)
+    .first()
+    .map(|&value| value as u32)
+}
+
+/// Get all virtual desktops from _NET_NUMBER_OF_DESKTOPS and _NET_DESKTOP_NAMES
+pub fn get_desktops() -> Result<Vec<DesktopInfo>> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let root = XDefaultRootWindow(display);
+    let count = get_root_cardinal(display, root, b"_NET_NUMBER_OF_DESKTOPS\0").unwrap_or(0);
+    let current = get_root_cardinal(display, root, b"_NET_CURRENT_DESKTOP\0");
+
+    // Names are a list of NUL-terminated UTF-8 strings
+    let names = get_property_string(
+      display,
+      root,
+      intern_atom(display, b"_NET_DESKTOP_NAMES\0"),
+      intern_atom(display, b"UTF8_STRING\0"),
+    )
+    .unwrap_or_default();
+    let mut names = names.split('\0');
+
+    let desktops = (0..count)
+      .map(|index| DesktopInfo {
+        index,
+        name: names.next().unwrap_or_default().to_string(),
+        current: current == Some(index),
+      })
+      .collect();
+
+    XCloseDisplay(display);
+    Ok(desktops)
+  }
+}
+
+/// Get the index of the currently shown virtual desktop
+pub fn get_current_desktop() -> Result<Option<u32>> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let current = get_root_cardinal(
+      display,
+      XDefaultRootWindow(display),
+      b"_NET_CURRENT_DESKTOP\0",
+    );
+
+    XCloseDisplay(display);
+    Ok(current)
+  }
+}
+
+/// Switch to another virtual desktop
+pub fn switch_desktop(index: u32) -> Result<()> {
+  unsafe {
+    use x11::xlib::CurrentTime;
+
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let root = XDefaultRootWindow(display);
+    send_client_message(
+      display,
+      root,
+      intern_atom(display, b"_NET_CURRENT_DESKTOP\0"),
+      [index as c_long, CurrentTime as c_long, 0, 0, 0],
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Move a window to another virtual desktop
+pub fn move_window_to_desktop(handle: i64, index: u32) -> Result<()> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    // Source indication 2: request comes from a pager/tool
+    send_client_message(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_WM_DESKTOP\0"),
+      [index as c_long, 2, 0, 0, 0],
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// _NET_WM_STATE atoms backing each window flag, in the order used by `flag_values`
+const WM_STATE_FLAG_ATOMS: [&[u8]; 7] = [
+  b"_NET_WM_STATE_STICKY\0",
+  b"_NET_WM_STATE_SKIP_TASKBAR\0",
+  b"_NET_WM_STATE_SKIP_PAGER\0",
+  b"_NET_WM_STATE_FULLSCREEN\0",
+  b"_NET_WM_STATE_BELOW\0",
+  b"_NET_WM_STATE_ABOVE\0",
+  b"_NET_WM_STATE_DEMANDS_ATTENTION\0",
+];
+
+/// Window flags in the order of WM_STATE_FLAG_ATOMS
+fn flag_values(flags: &WindowFlags) -> [Option<bool>; 7] {
+  [
+    flags.sticky,
+    flags.skip_taskbar,
+    flags.skip_pager,
+    flags.fullscreen,
+    flags.below,
+    flags.above,
+    flags.demands_attention,
+  ]
+}
+
+/// Read the atoms currently listed in a window's _NET_WM_STATE
+fn get_wm_state(display: *mut Display, window: Window) -> Vec<Atom> {
+  get_property_longs(
+    display,
+    window,
+    intern_atom(display, b"_NET_WM_STATE\0"),
+    XA_ATOM,
+  )
+}
+
+/// Add (1) or remove (0) a _NET_WM_STATE atom through the window manager
+fn change_wm_state(display: *mut Display, window: Window, action: c_long, state: Atom) {
+  // Source indication 2: request comes from a pager/tool
+  send_client_message(
+    display,
+    window,
+    intern_atom(display, b"_NET_WM_STATE\0"),
+    [action, state as c_long, 0, 2, 0],
+  );
+}
+
+/// Get the window manager flags currently set on a window
+pub fn get_window_flags(handle: i64) -> Result<WindowFlags> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let window = handle as Window;
+    let state = get_wm_state(display, window);
+    let values: Vec<bool> = WM_STATE_FLAG_ATOMS
+      .iter()
+      .map(|name| state.contains(&intern_atom(display, name)))
+      .collect();
+
+    // Being on all desktops (_NET_WM_DESKTOP = 0xFFFFFFFF) also counts as sticky
+    let all_desktops = get_property_longs(
+      display,
+      window,
+      intern_atom(display, b"_NET_WM_DESKTOP\0"),
+      XA_CARDINAL,
+    )
+    .first()
+    .is_some_and(|&desktop| desktop as u32 == u32::MAX);
+
+    XCloseDisplay(display);
+    Ok(WindowFlags {
+      sticky: Some(values[0] || all_desktops),
+      skip_taskbar: Some(values[1]),
+      skip_pager: Some(values[2]),
+      fullscreen: Some(values[3]),
+      below: Some(values[4]),
+      above: Some(values[5]),
+      demands_attention: Some(values[6]),
+    })
+  }
+}
+
+/// Add (`true`) or remove (`false`) window manager flags, omitted flags are left untouched
+pub fn set_window_flags(handle: i64, flags: &WindowFlags) -> Result<()> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let window = handle as Window;
+
+    for (value, name) in flag_values(flags).iter().zip(WM_STATE_FLAG_ATOMS) {
+      if let Some(enable) = value {
+        change_wm_state(
+          display,
+          window,
+          if *enable { 1 } else { 0 },
+          intern_atom(display, name),
+        );
+      }
+    }
+
+    // _NET_WM_STATE_STICKY only pins the viewport, workspaces are handled by _NET_WM_DESKTOP
+    if let Some(sticky) = flags.sticky {
+      let root = XDefaultRootWindow(display);
+      let desktop = if sticky {
+        Some(u32::MAX)
+      } else {
+        get_root_cardinal(display, root, b"_NET_CURRENT_DESKTOP\0")
+      };
+
+      if let Some(desktop) = desktop {
+        send_client_message(
+          display,
+          window,
+          intern_atom(display, b"_NET_WM_DESKTOP\0"),
+          [desktop as c_long, 2, 0, 0, 0],
+        );
+      }
+    }
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Get the current window state
+pub fn get_window_state(handle: i64) -> Result<Option<WindowStateInfo>> {
+  with_thread_display(|display| unsafe {
+    let window = handle as Window;
+
+    let mut attrs: XWindowAttributes = std::mem::zeroed();
+    if XGetWindowAttributes(display, window, &mut attrs) == 0 {
+      return Ok(None);
+    }
+
+    let active = get_active_client(display, XDefaultRootWindow(display));
+    let state = read_window_state(display, window, active);
+
+    Ok(Some(state))
+  })
+}
+
+/// Set the window state through ICCCM/EWMH requests to the window manager
+pub fn set_window_state(handle: i64, state: WindowState) -> Result<()> {
+  use x11::xlib::{XDefaultScreen, XIconifyWindow, XMapWindow, XWithdrawWindow};
+
+  with_thread_display(|display| unsafe {
+    let window = handle as Window;
+    let maximized_vert = intern_atom(display, b"_NET_WM_STATE_MAXIMIZED_VERT\0");
+    let maximized_horz = intern_atom(display, b"_NET_WM_STATE_MAXIMIZED_HORZ\0");
+    let fullscreen = intern_atom(display, b"_NET_WM_STATE_FULLSCREEN\0");
+
+    match state {
+      WindowState::Minimize => {
+        XIconifyWindow(display, window, XDefaultScreen(display));
+      }
+      WindowState::Maximize => {
+        change_wm_state(display, window, 1, maximized_vert);
+        change_wm_state(display, window, 1, maximized_horz);
+      }
+      WindowState::Restore => {
+        // Mapping an iconic window returns it to the normal state, hidden windows stay hidden
+        if get_icccm_state(display, window) == Some(ICONIC_STATE) {
+          XMapWindow(display, window);
+        }
+        change_wm_state(display, window, 0, maximized_vert);
+        change_wm_state(display, window, 0, maximized_horz);
+      }
+      WindowState::Fullscreen => change_wm_state(display, window, 1, fullscreen),
+      WindowState::ExitFullscreen => change_wm_state(display, window, 0, fullscreen),
+      WindowState::Hide => {
+        // Also sends the synthetic UnmapNotify the window manager needs to withdraw a client
+        XWithdrawWindow(display, window, XDefaultScreen(display));
+      }
+      WindowState::Show => {
+        XMapWindow(display, window);
+      }
+    }
+
+    XFlush(display);
+    Ok(())
+  })
+}
+
+/// Set the window type hint through _NET_WM_WINDOW_TYPE
+pub fn set_window_type(handle: i64, window_type: WindowType) -> Result<()> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let name = WINDOW_TYPE_ATOMS
+      .iter()
+      .find(|(t, _)| *t == window_type)
+      .map(|(_, name)| *name)
+      .unwrap_or(b"_NET_WM_WINDOW_TYPE_NORMAL\0");
+    let type_atom = intern_atom(display, name);
+
+    XChangeProperty(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_WM_WINDOW_TYPE\0"),
+      XA_ATOM,
+      32,
+      PropModeReplace,
+      &type_atom as *const Atom as *const u8,
+      1,
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Remove or restore window decorations through _MOTIF_WM_HINTS
+pub fn set_decorations(handle: i64, enabled: bool) -> Result<()> {
+  // MWM_HINTS_DECORATIONS flag, hints are flags/functions/decorations/input mode/status
+  const MWM_HINTS_DECORATIONS: c_ulong = 1 << 1;
+
+  unsafe {
+    use x11::xlib::XDeleteProperty;
+
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let window = handle as Window;
+    let motif_hints = intern_atom(display, b"_MOTIF_WM_HINTS\0");
+    let current = get_property_longs(display, window, motif_hints, motif_hints);
+
+    let mut saved = DECORATION_RESTORE
+      .lock()
+      .map_err(|_| Error::new(Status::GenericFailure, "Decoration state is poisoned"))?;
+
+    let hints: Option<Vec<c_ulong>> = if enabled {
+      match saved.remove(&handle) {
+        Some(original) => original,
+        // Nothing saved: explicitly ask for all decorations
+        None => Some(vec![MWM_HINTS_DECORATIONS, 0, 1, 0, 0]),
+      }
+    } else {
+      saved
+        .entry(handle)
+        .or_insert_with(|| (current.len() >= 5).then(|| current.clone()));
+
+      let mut hints = if current.len() >= 5 {
+        current[..5].to_vec()
+      } else {
+        vec![0; 5]
+      };
+      hints[0] |= MWM_HINTS_DECORATIONS;
+      hints[2] = 0;
+      Some(hints)
+    };
+
+    match hints {
+      Some(hints) => {
+        XChangeProperty(
+          display,
+          window,
+          motif_hints,
+          motif_hints,
+          32,
+          PropModeReplace,
+          hints.as_ptr() as *const u8,
+          hints.len() as c_int,
+        );
+      }
+      None => {
+        XDeleteProperty(display, window, motif_hints);
+      }
+    }
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
+
+/// Query the pointer position and button/modifier mask on the root window
+fn query_pointer() -> Result<(c_int, c_int, c_uint)> {
+  // Polled in loops, so reuse one connection per thread
+  with_thread_display(|display| unsafe {
+    let mut root_return: Window = 0;
+    let mut child_return: Window = 0;
+    let mut root_x: c_int = 0;
+    let mut root_y: c_int = 0;
+    let mut win_x: c_int = 0;
+    let mut win_y: c_int = 0;
+    let mut mask: c_uint = 0;
+    XQueryPointer(
+      display,
+      XDefaultRootWindow(display),
+      &mut root_return,
+      &mut child_return,
+      &mut root_x,
+      &mut root_y,
+      &mut win_x,
+      &mut win_y,
+      &mut mask,
+    );
+    Ok((root_x, root_y, mask))
+  })
+}
+
+/// Convert an X modifier state to `utils::MOD_*` bits
+fn modifier_bits(mask: c_uint) -> u32 {
+  let mut modifiers = 0;
+  if mask & ShiftMask != 0 {
+    modifiers |= utils::MOD_SHIFT;
+  }
+  if mask & ControlMask != 0 {
+    modifiers |= utils::MOD_CONTROL;
+  }
+  if mask & Mod1Mask != 0 {
+    modifiers |= utils::MOD_ALT;
+  }
+  if mask & Mod4Mask != 0 {
+    modifiers |= utils::MOD_SUPER;
+  }
+  modifiers
+}
+
+/// Get the modifier keys currently held, as `utils::MOD_*` bits
+pub fn get_pressed_modifiers() -> Result<u32> {
+  let (_, _, mask) = query_pointer()?;
+  Ok(modifier_bits(mask))
+}
+
+/// Get the pointer position and button/modifier state (monitor is filled by the caller)
+pub fn get_cursor_position() -> Result<CursorPosition> {
+  let (x, y, mask) = query_pointer()?;
+  Ok(CursorPosition {
+    x,
+    y,
+    monitor: None,
+    left_button: mask & Button1Mask != 0,
+    middle_button: mask & Button2Mask != 0,
+    right_button: mask & Button3Mask != 0,
+    modifiers: utils::modifier_names(modifier_bits(mask)),
+  })
+}
+
+/// Translate common accelerator key names to X keysym names
+fn keysym_name(key: &str) -> String {
+  let lower = key.to_ascii_lowercase();
+  let name = match lower.as_str() {
+    "esc" | "escape" => "Escape",
+    "enter" | "return" => "Return",
+    "space" => "space",
+    "tab" => "Tab",
+    "backspace" => "BackSpace",
+    "delete" | "del" => "Delete",
+    "insert" | "ins" => "Insert",
+    "home" => "Home",
+    "end" => "End",
+    "pageup" => "Prior",
+    "pagedown" => "Next",
+    "up" => "Up",
+    "down" => "Down",
+    "left" => "Left",
+    "right" => "Right",
+    "printscreen" | "print" => "Print",
+    "plus" => "plus",
+    _ if lower.len() > 1 && lower.starts_with('f') && lower[1..].parse::<u8>().is_ok() => {
+      return lower.to_ascii_uppercase();
+    }
+    _ if lower.chars().count() == 1 => return lower,
+    // Anything else is taken as a raw keysym name (e.g. XF86AudioPlay)
+    _ => return key.to_string(),
+  };
+  name.to_string()
+}
+
+/// Grab `keycode` with `modifiers` on the root window, whatever the Caps/Num Lock state
+unsafe fn grab_hotkey(display: *mut Display, keycode: c_int, modifiers: c_uint) -> bool {
+  let root = XDefaultRootWindow(display);
+
+  // Another client holding the key makes the server answer with BadAccess
+  let ((), failed) = trap_x_errors(display, || {
+    for extra in [0, LockMask, Mod2Mask, LockMask | Mod2Mask] {
+      XGrabKey(
+        display,
+        keycode,
+        modifiers | extra,
+        root,
+        1,
+        GrabModeAsync,
+        GrabModeAsync,
+      );
+    }
+  });
+
+  if failed {
+    ungrab_hotkey(display, keycode, modifiers);
+    return false;
+  }
+  true
+}
+
+unsafe fn ungrab_hotkey(display: *mut Display, keycode: c_int, modifiers: c_uint) {
+  let root = XDefaultRootWindow(display);
+  for extra in [0, LockMask, Mod2Mask, LockMask | Mod2Mask] {
+    XUngrabKey(display, keycode, modifiers | extra, root);
+  }
+  XSync(display, 0);
+}
+
+/// Write end of the pipe waking the hotkey thread, -1 while it is not running
+static HOTKEY_WAKE: AtomicI32 = AtomicI32::new(-1);
+
+/// Wake the hotkey thread so it handles the commands just sent
+pub fn wake_hotkey_loop() {
+  let fd = HOTKEY_WAKE.load(Ordering::SeqCst);
+  if fd >= 0 {
+    unsafe {
+      libc::write(fd, [1u8].as_ptr() as *const _, 1);
+    }
+  }
+}
+
+/// Own the hotkey grabs and deliver key presses until the command channel closes
+pub fn run_hotkey_loop(commands: Receiver<HotkeyCommand>) {
+  unsafe {
+    let Some(display) = get_display() else {
+      for command in commands {
+        if let HotkeyCommand::Register { reply, .. } = command {
+          let _ = reply.send(Err(Error::new(
+            Status::GenericFailure,
+            "Cannot open X11 display",
+          )));
+        }
+      }
+      return;
+    };
+
+    // `wake_hotkey_loop` writes to this pipe so commands are handled without waiting for X events
+    let mut pipe: [c_int; 2] = [-1, -1];
+    if libc::pipe2(pipe.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) != 0 {
+      pipe = [-1, -1];
+    }
+    let [wake_read, wake_write] = pipe;
+    HOTKEY_WAKE.store(wake_write, Ordering::SeqCst);
+    // Without a pipe, commands are picked up between periodic checks
+    let timeout = (wake_read < 0).then_some(Duration::from_millis(100));
+
+    // (keycode, modifier mask) of each grabbed hotkey
+    let mut grabs: HashMap<u32, (c_int, c_uint)> = HashMap::new();
+
+    loop {
+      loop {
+        match commands.try_recv() {
+          Ok(HotkeyCommand::Register {
+            id,
+            modifiers,
+            key,
+            reply,
+          }) => {
+            let name = CString::new(keysym_name(&key)).unwrap_or_default();
+            let keysym = XStringToKeysym(name.as_ptr());
+            let keycode = if keysym == 0 {
+              0
+            } else {
+              XKeysymToKeycode(display, keysym) as c_int
+            };
+
+            let result = if keycode == 0 {
+              Err(Error::new(
+                Status::InvalidArg,
+                format!("Unknown key: {}", key),
+              ))
+            } else {
+              let mut mask = 0;
+              if modifiers & utils::MOD_SHIFT != 0 {
+                mask |= ShiftMask;
+              }
+              if modifiers & utils::MOD_CONTROL != 0 {
+                mask |= ControlMask;
+              }
+              if modifiers & utils::MOD_ALT != 0 {
+                mask |= Mod1Mask;
+              }
+              if modifiers & utils::MOD_SUPER != 0 {
+                mask |= Mod4Mask;
+              }
+
+              if grab_hotkey(display, keycode, mask) {
+                grabs.insert(id, (keycode, mask));
+                Ok(())
+              } else {
+                Err(Error::new(
+                  Status::GenericFailure,
+                  "Hotkey is already grabbed by another application",
+                ))
+              }
+            };
+            let _ = reply.send(result);
+          }
+          Ok(HotkeyCommand::Unregister { id }) => {
+            if let Some((keycode, mask)) = grabs.remove(&id) {
+              ungrab_hotkey(display, keycode, mask);
+            }
+          }
+          Err(TryRecvError::Empty) => break,
+          Err(TryRecvError::Disconnected) => {
+            let _ =
+              HOTKEY_WAKE.compare_exchange(wake_write, -1, Ordering::SeqCst, Ordering::SeqCst);
+            for fd in pipe.into_iter().filter(|&fd| fd >= 0) {
+              libc::close(fd);
+            }
+            XCloseDisplay(display);
+            return;
+          }
+        }
+      }
+
+      while XPending(display) > 0 {
+        let mut event: XEvent = std::mem::zeroed();
+        XNextEvent(display, &mut event);
+        if event.get_type() != KeyPress {
+          continue;
+        }
+
+        let key = event.key;
+        let state = key.state & !(LockMask | Mod2Mask);
+        let pressed = grabs
+          .iter()
+          .find(|(_, &(keycode, mask))| keycode == key.keycode as c_int && mask == state)
+          .map(|(&id, _)| id);
+        if let Some(id) = pressed {
+          hotkey::dispatch(id);
+        }
+      }
+
+      wait_for_x_events(display, wake_read, timeout);
+      let mut drained = [0u8; 64];
+      while wake_read >= 0
+        && libc::read(wake_read, drained.as_mut_ptr() as *mut _, drained.len()) > 0
+      {}
+    }
+  }
+}
+
+/// Walk up the window tree to the top-level ancestor, the window manager frame if reparented
+fn get_frame_window(display: *mut Display, window: Window) -> Window {
+  unsafe {
+    let mut current = window;
+    loop {
+      let mut root: Window = 0;
+      let mut parent: Window = 0;
+      let mut children: *mut Window = ptr::null_mut();
+      let mut count: c_uint = 0;
+
+      if XQueryTree(
+        display,
+        current,
+        &mut root,
+        &mut parent,
+        &mut children,
+        &mut count,
+      ) == 0
+      {
+        return current;
+      }
+      if !children.is_null() {
+        XFree(children as *mut _);
+      }
+
+      if parent == 0 || parent == root {
+        return current;
+      }
+      current = parent;
+    }
+  }
+}
+
+/// Scale a channel extracted with `mask` to 8 bits
+fn channel_value(pixel: c_ulong, mask: c_ulong) -> u8 {
+  if mask == 0 {
+    return 0;
+  }
+  let value = (pixel & mask) >> mask.trailing_zeros();
+  let bits = (mask >> mask.trailing_zeros()).count_ones();
+  if bits >= 8 {
+    (value >> (bits - 8)) as u8
+  } else {
+    (value * 255 / ((1 << bits) - 1)) as u8
+  }
+}
+
+/// Convert a ZPixmap image to tightly packed RGBA
+unsafe fn image_to_rgba(image: *mut XImage) -> Vec<u8> {
+  let img = &*image;
+  let (width, height) = (img.width.max(0) as usize, img.height.max(0) as usize);
+  let mut rgba = Vec::with_capacity(width * height * 4);
+
+  for y in 0..height {
+    for x in 0..width {
+      let pixel = if img.bits_per_pixel == 32 {
+        let offset = y * img.bytes_per_line as usize + x * 4;
+        let bytes = std::slice::from_raw_parts(img.data.add(offset) as *const u8, 4);
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if img.byte_order == LSBFirst {
+          u32::from_le_bytes(bytes) as c_ulong
+        } else {
+          u32::from_be_bytes(bytes) as c_ulong
+        }
+      } else {
+        XGetPixel(image, x as c_int, y as c_int)
+      };
+
+      rgba.push(channel_value(pixel, img.red_mask));
+      rgba.push(channel_value(pixel, img.green_mask));
+      rgba.push(channel_value(pixel, img.blue_mask));
+      // Only ARGB visuals carry an alpha channel, in the bits left over by the colors
+      rgba.push(if img.depth == 32 {
+        (pixel >> 24) as u8
+      } else {
+        255
+      });
+    }
+  }
+  rgba
+}
+
+/// Plane mask selecting every bit of the pixels
+const ALL_PLANES: c_ulong = !0;
+
+// The x11 crate declares the MIT-SHM functions but only links libXext through its "dpms" feature
+#[link(name = "Xext")]
+extern "C" {}
+
+/// Grab an area of a drawable through a shared memory segment
+/// Returns None when MIT-SHM cannot be used (e.g. on a remote display)
+unsafe fn grab_pixels_shm(
+  display: *mut Display,
+  drawable: Window,
+  attrs: &XWindowAttributes,
+  area: &Rect,
+) -> Option<Vec<u8>> {
+  if XShmQueryExtension(display) == 0 {
+    return None;
+  }
+
+  let mut info: XShmSegmentInfo = std::mem::zeroed();
+  let image = XShmCreateImage(
+    display,
+    attrs.visual,
+    attrs.depth as c_uint,
+    ZPixmap,
+    ptr::null_mut(),
+    &mut info,
+    area.width as c_uint,
+    area.height as c_uint,
+  );
+  if image.is_null() {
+    return None;
+  }
+
+  let size = (*image).bytes_per_line as usize * area.height as usize;
+  info.shmid = libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600);
+  if info.shmid < 0 {
+    XDestroyImage(image);
+    return None;
+  }
+
+  info.shmaddr = libc::shmat(info.shmid, ptr::null(), 0) as *mut c_char;
+  if info.shmaddr as isize == -1 {
+    libc::shmctl(info.shmid, libc::IPC_RMID, ptr::null_mut());
+    XDestroyImage(image);
+    return None;
+  }
+  (*image).data = info.shmaddr;
+  info.readOnly = 0;
+
+  let (grabbed, failed) = trap_x_errors(display, || {
+    XShmAttach(display, &mut info) != 0
+      && XShmGetImage(
+        display,
+        drawable,
+        image,
+        area.x,
+        area.y,
+        ALL_PLANES as c_uint,
+      ) != 0
+  });
+  let pixels = (grabbed && !failed).then(|| image_to_rgba(image));
+
+  trap_x_errors(display, || XShmDetach(display, &mut info));
+  (*image).data = ptr::null_mut();
+  XDestroyImage(image);
+  libc::shmdt(info.shmaddr as *const libc::c_void);
+  libc::shmctl(info.shmid, libc::IPC_RMID, ptr::null_mut());
+
+  pixels
+}
+
+/// Grab an area of a drawable as RGBA, preferring MIT-SHM
+unsafe fn grab_pixels(
+  display: *mut Display,
+  drawable: Window,
+  attrs: &XWindowAttributes,
+  area: &Rect,
+) -> Result<Vec<u8>> {
+  if let Some(pixels) = grab_pixels_shm(display, drawable, attrs, area) {
+    return Ok(pixels);
+  }
+
+  let (image, failed) = trap_x_errors(display, || {
+    XGetImage(
+      display,
+      drawable,
+      area.x,
+      area.y,
+      area.width as c_uint,
+      area.height as c_uint,
+      ALL_PLANES,
+      ZPixmap,
+    )
+  });
+  if image.is_null() {
+    return Err(Error::new(Status::GenericFailure, "XGetImage failed"));
+  }
+  if failed {
+    XDestroyImage(image);
+    return Err(Error::new(Status::GenericFailure, "XGetImage failed"));
+  }
+
+  let pixels = image_to_rgba(image);
+  XDestroyImage(image);
+  Ok(pixels)
+}
+
+/// Capture a region of an already resolved window on an open display
+unsafe fn capture_drawable(
+  display: *mut Display,
+  window: Window,
+  region: Option<Rect>,
+) -> Result<RgbaImage> {
+  let mut attrs: XWindowAttributes = std::mem::zeroed();
+  let (found, _) = trap_x_errors(display, || {
+    XGetWindowAttributes(display, window, &mut attrs) != 0
+  });
+  if !found {
+    return Err(Error::new(Status::InvalidArg, "Window not found"));
+  }
+  if attrs.map_state != IsViewable {
+    return Err(Error::new(Status::GenericFailure, "Window is not viewable"));
+  }
+
+  let full = Rect {
+    x: 0,
+    y: 0,
+    width: attrs.width,
+    height: attrs.height,
+  };
+  let area = match region {
+    Some(region) => region
+      .intersection(&full)
+      .ok_or_else(|| Error::new(Status::InvalidArg, "Region is outside the window"))?,
+    None => full,
+  };
+
+  // Without backing store only the part of a window inside the root can be read,
+  // anything past the screen edges makes the grab fail with BadMatch
+  let mut x: c_int = 0;
+  let mut y: c_int = 0;
+  let mut child: Window = 0;
+  XTranslateCoordinates(
+    display, window, attrs.root, 0, 0, &mut x, &mut y, &mut child,
+  );
+  let root = Rect {
+    x: 0,
+    y: 0,
+    width: (*attrs.screen).width,
+    height: (*attrs.screen).height,
+  };
+  let area = area
+    .clip_relative(x, y, &root)
+    .ok_or_else(|| Error::new(Status::GenericFailure, "Region is off screen"))?;
+
+  let pixels = grab_pixels(display, window, &attrs, &area)?;
+  Ok(RgbaImage {
+    width: area.width as u32,
+    height: area.height as u32,
+    data: pixels.into(),
+  })
+}
+
+/// Capture the pixels of a window (or of a region of it) as RGBA
+pub fn capture_window(handle: i64, region: Option<Rect>, include_frame: bool) -> Result<RgbaImage> {
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let window = if include_frame {
+      get_frame_window(display, handle as Window)
+    } else {
+      handle as Window
+    };
+
+    let image = capture_drawable(display, window, region);
+    XCloseDisplay(display);
+    image
+  }
+}
+
+// XDamage is not covered by the x11 crate
+type Damage = c_ulong;
+
+/// Report a single event when the damage region becomes non-empty
+const X_DAMAGE_REPORT_NON_EMPTY: c_int = 3;
+/// DamageNotify, relative to the extension event base
+const X_DAMAGE_NOTIFY: c_int = 0;
+
+#[link(name = "Xdamage")]
+extern "C" {
+  fn XDamageQueryExtension(
+    display: *mut Display,
+    event_base: *mut c_int,
+    error_base: *mut c_int,
+  ) -> c_int;
+  fn XDamageCreate(display: *mut Display, drawable: Window, level: c_int) -> Damage;
+  fn XDamageDestroy(display: *mut Display, damage: Damage);
+  fn XDamageSubtract(display: *mut Display, damage: Damage, repair: c_ulong, parts: c_ulong);
+}
+
+/// Capture a window each time its content changes, at most once per `min_interval`
+/// `ready` receives the setup result; `on_frame` returns false when the frame was dropped
+pub fn watch_window_frames(
+  handle: i64,
+  region: Option<Rect>,
+  include_frame: bool,
+  min_interval: Duration,
+  stop: &AtomicBool,
+  ready: Sender<Result<()>>,
+  mut on_frame: impl FnMut(RgbaImage) -> bool,
+) {
+  unsafe {
+    let Some(display) = get_display() else {
+      let _ = ready.send(Err(Error::new(
+        Status::GenericFailure,
+        "Cannot open X11 display",
+      )));
+      return;
+    };
+
+    let mut event_base: c_int = 0;
+    let mut error_base: c_int = 0;
+    if XDamageQueryExtension(display, &mut event_base, &mut error_base) == 0 {
+      XCloseDisplay(display);
+      let _ = ready.send(Err(Error::new(
+        Status::GenericFailure,
+        "XDamage extension is not available",
+      )));
+      return;
+    }
+
+    let window = if include_frame {
+      get_frame_window(display, handle as Window)
+    } else {
+      handle as Window
+    };
+
+    let (damage, failed) = trap_x_errors(display, || {
+      XDamageCreate(display, window, X_DAMAGE_REPORT_NON_EMPTY)
+    });
+    if failed {
+      XCloseDisplay(display);
+      let _ = ready.send(Err(Error::new(Status::InvalidArg, "Window not found")));
+      return;
+    }
+    let _ = ready.send(Ok(()));
+
+    // Start with a frame of the current content
+    let mut dirty = true;
+    let mut last_frame: Option<Instant> = None;
+
+    while !stop.load(Ordering::Relaxed) {
+      while XPending(display) > 0 {
+        let mut event: XEvent = std::mem::zeroed();
+        XNextEvent(display, &mut event);
+        if event.get_type() == event_base + X_DAMAGE_NOTIFY {
+          dirty = true;
+        }
+      }
+
+      let due = last_frame.is_none_or(|t| t.elapsed() >= min_interval);
+      if dirty && due {
+        // Clear the damage first so changes made while grabbing raise a new event
+        trap_x_errors(display, || XDamageSubtract(display, damage, 0, 0));
+
+        match capture_drawable(display, window, region) {
+          Ok(frame) => {
+            // A dropped frame is retried so the latest content always gets through
+            dirty = !on_frame(frame);
+            last_frame = Some(Instant::now());
+          }
+          Err(_) => {
+            let mut attrs: XWindowAttributes = std::mem::zeroed();
+            let (exists, _) = trap_x_errors(display, || {
+              XGetWindowAttributes(display, window, &mut attrs) != 0
+            });
+            if !exists {
+              break;
+            }
+            // Unmapped or shrunk past the region, wait for the next change
+            dirty = false;
+          }
+        }
+      }
+
+      // Sleep until the next damage event, or until a pending frame is due
+      let timeout = match last_frame {
+        Some(t) if dirty => min_interval
+          .saturating_sub(t.elapsed())
+          .min(STOP_CHECK_INTERVAL),
+        _ => STOP_CHECK_INTERVAL,
+      };
+      wait_for_x_events(display, -1, Some(timeout));
+    }
+
+    trap_x_errors(display, || XDamageDestroy(display, damage));
+    XCloseDisplay(display);
+  }
+}
+
+/// Show or hide an attached overlay
+pub fn set_overlay_visible(handle: i64, visible: bool) -> Result<()> {
+  set_window_state(
+    handle,
+    if visible {
+      WindowState::Show
+    } else {
+      WindowState::Hide
+    },
+  )
+}
+
+/// Call `on_change` whenever the geometry, stacking, state or focus of a window may have changed
+/// Returns once `stop` is set or the window is destroyed; `ready` receives the setup result
+pub fn watch_window_changes(
+  handle: i64,
+  stop: &AtomicBool,
+  ready: Sender<Result<()>>,
+  mut on_change: impl FnMut(),
+) {
+  use x11::xlib::{
+    ConfigureNotify, DestroyNotify, MapNotify, PropertyChangeMask, PropertyNotify,
+    StructureNotifyMask, UnmapNotify, XSelectInput,
+  };
+
+  unsafe {
+    let Some(display) = get_display() else {
+      let _ = ready.send(Err(Error::new(
+        Status::GenericFailure,
+        "Cannot open X11 display",
+      )));
+      return;
+    };
+
+    let window = handle as Window;
+    let root = XDefaultRootWindow(display);
+
+    // Moves and restacks are reported on the WM frame, state changes on the client
+    let (_, failed) = trap_x_errors(display, || {
+      let frame = get_frame_window(display, window);
+      XSelectInput(display, window, StructureNotifyMask | PropertyChangeMask);
+      if frame != window {
+        XSelectInput(display, frame, StructureNotifyMask);
+      }
+    });
+    if failed {
+      XCloseDisplay(display);
+      let _ = ready.send(Err(Error::new(Status::InvalidArg, "Window not found")));
+      return;
+    }
+    XSelectInput(display, root, PropertyChangeMask);
+    let active_window = intern_atom(display, b"_NET_ACTIVE_WINDOW\0");
+    // Other client properties, such as _NET_WM_USER_TIME, change on every input
+    let state_atoms = [
+      intern_atom(display, b"_NET_WM_STATE\0"),
+      intern_atom(display, b"WM_STATE\0"),
+    ];
+    let _ = ready.send(Ok(()));
+
+    on_change();
+
+    while !stop.load(Ordering::Relaxed) {
+      let mut changed = false;
+      let mut destroyed = false;
+
+      while XPending(display) > 0 {
+        let mut event: XEvent = std::mem::zeroed();
+        XNextEvent(display, &mut event);
+        let event_type = event.get_type();
+        if event_type == DestroyNotify {
+          destroyed |= event.destroy_window.window == window;
+        } else if event_type == PropertyNotify {
+          changed |= if event.property.window == root {
+            event.property.atom == active_window
+          } else {
+            state_atoms.contains(&event.property.atom)
+          };
+        } else if [ConfigureNotify, MapNotify, UnmapNotify].contains(&event_type) {
+          changed = true;
+        }
+      }
+
+      if destroyed {
+        break;
+      }
+      if changed {
+        let mut attrs: XWindowAttributes = std::mem::zeroed();
+        let (exists, _) = trap_x_errors(display, || {
+          XGetWindowAttributes(display, window, &mut attrs) != 0
+        });
+        if !exists {
+          break;
+        }
+        on_change();
+      }
+
+      wait_for_x_events(display, -1, Some(STOP_CHECK_INTERVAL));
+    }
+
+    XCloseDisplay(display);
+  }
+}
+
+/// Report windows appearing, being renamed or destroyed until `stop` is set
+/// `ready` receives the setup result; events are only sent after it
+pub fn watch_windows(
+  stop: &AtomicBool,
+  ready: Sender<Result<()>>,
+  mut on_event: impl FnMut(WindowEvent),
+) {
+  use x11::xlib::{
+    MapNotify, PropertyChangeMask, PropertyNotify, StructureNotifyMask, XSelectInput,
+  };
+
+  unsafe {
+    let Some(display) = get_display() else {
+      let _ = ready.send(Err(Error::new(
+        Status::GenericFailure,
+        "Cannot open X11 display",
+      )));
+      return;
+    };
+
+    let root = XDefaultRootWindow(display);
+    let client_list = intern_atom(display, b"_NET_CLIENT_LIST\0");
+    let names = [
+      intern_atom(display, b"_NET_WM_NAME\0"),
+      intern_atom(display, b"WM_NAME\0"),
+    ];
+
+    // Skips windows destroyed before their event was read, querying them would be fatal
+    let report = |on_event: &mut dyn FnMut(WindowEvent), window: Window| {
+      let mut attrs: XWindowAttributes = std::mem::zeroed();
+      let (exists, _) = trap_x_errors(display, || {
+        XGetWindowAttributes(display, window, &mut attrs) != 0
+      });
+      if exists {
+        on_event(WindowEvent::Changed(window as i64));
+      }
+    };
+
+    // Clients report renames and mapping, the root reports clients coming and going
+    let follow = |window: Window| {
+      trap_x_errors(display, || {
+        XSelectInput(display, window, PropertyChangeMask | StructureNotifyMask)
+      });
+    };
+    XSelectInput(display, root, PropertyChangeMask);
+    let mut clients: Vec<Window> = get_client_list(display, root);
+    clients.iter().for_each(|&window| follow(window));
+    let _ = ready.send(Ok(()));
+
+    while !stop.load(Ordering::Relaxed) {
+      while XPending(display) > 0 {
+        let mut event: XEvent = std::mem::zeroed();
+        XNextEvent(display, &mut event);
+        let event_type = event.get_type();
+
+        if event_type == PropertyNotify && event.property.window == root {
+          if event.property.atom != client_list {
+            continue;
+          }
+          let current = get_client_list(display, root);
+          for &window in clients.iter().filter(|w| !current.contains(w)) {
+            on_event(WindowEvent::Closed(window as i64));
+          }
+          for &window in current.iter().filter(|w| !clients.contains(w)) {
+            follow(window);
+            report(&mut on_event, window);
+          }
+          clients = current;
+        } else if event_type == PropertyNotify && names.contains(&event.property.atom) {
+          report(&mut on_event, event.property.window);
+        } else if event_type == MapNotify && clients.contains(&event.map.window) {
+          report(&mut on_event, event.map.window);
+        }
+      }
+
+      wait_for_x_events(display, -1, Some(STOP_CHECK_INTERVAL));
+    }
+
+    XCloseDisplay(display);
+  }
+}
+
+/// Read every image of _NET_WM_ICON, converting the ARGB cardinals to RGBA
+pub fn get_window_icons(handle: i64) -> Result<Vec<RgbaImage>> {
+  let values = unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    let values = get_property_longs(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_WM_ICON\0"),
+      XA_CARDINAL,
+    );
+
+    XCloseDisplay(display);
+    values
+  };
+
+  // The property is a sequence of width, height and width * height pixels
+  let mut icons = Vec::new();
+  let mut rest = &values[..];
+  while rest.len() >= 2 {
+    let (width, height) = (rest[0] as usize, rest[1] as usize);
+    let count = width.saturating_mul(height);
+    if count == 0 || rest.len() - 2 < count {
+      break;
+    }
+
+    let mut rgba = Vec::with_capacity(count * 4);
+    for &argb in &rest[2..2 + count] {
+      let argb = argb as u32;
+      rgba.extend_from_slice(&[
+        (argb >> 16) as u8,
+        (argb >> 8) as u8,
+        argb as u8,
+        (argb >> 24) as u8,
+      ]);
+    }
+
+    icons.push(RgbaImage {
+      width: width as u32,
+      height: height as u32,
+      data: rgba.into(),
+    });
+    rest = &rest[2 + count..];
+  }
+
+  Ok(icons)
+}
+
+/// Write images to _NET_WM_ICON as ARGB cardinals
+pub fn set_window_icon(handle: i64, images: &[RgbaImage]) -> Result<()> {
+  let mut values: Vec<c_ulong> = Vec::new();
+  for image in images {
+    values.push(image.width as c_ulong);
+    values.push(image.height as c_ulong);
+    values.extend(image.data.chunks_exact(4).map(|p| {
+      ((p[3] as c_ulong) << 24)
+        | ((p[0] as c_ulong) << 16)
+        | ((p[1] as c_ulong) << 8)
+        | p[2] as c_ulong
+    }));
+  }
+
+  unsafe {
+    let display =
+      get_display().ok_or_else(|| Error::new(Status::GenericFailure, "Cannot open X11 display"))?;
+
+    // Format-32 data is handed to Xlib as C longs
+    XChangeProperty(
+      display,
+      handle as Window,
+      intern_atom(display, b"_NET_WM_ICON\0"),
+      XA_CARDINAL,
+      32,
+      PropModeReplace,
+      values.as_ptr() as *const u8,
+      values.len() as c_int,
+    );
+
+    XFlush(display);
+    XCloseDisplay(display);
+  }
+  Ok(())
+}
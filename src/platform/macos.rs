@@ -1,5 +1,5 @@
 #![allow(unexpected_cfgs)]
-use crate::WindowInfo;
+use crate::{WindowInfo, WindowType};
 use napi::bindgen_prelude::*;
 
 use cocoa::base::{id, nil};
@@ -71,6 +71,9 @@ pub fn get_windows() -> Result<Vec<WindowInfo>> {
     let windows_array: CFArray = TCFType::wrap_under_create_rule(window_list);
     let mut result = Vec::new();
 
+    // The window list is ordered front to back
+    let count = windows_array.len() as i32;
+
     for i in 0..windows_array.len() {
       let window_ptr = match windows_array.get(i) {
         Some(p) => *p as *const c_void,
@@ -172,6 +175,14 @@ pub fn get_windows() -> Result<Vec<WindowInfo>> {
         width,
         height,
         path: String::new(),
+        z_index: count - 1 - i as i32,
+        desktop: -1,
+        minimized: false,
+        maximized: false,
+        fullscreen: false,
+        focused: false,
+        always_on_top: false,
+        window_type: WindowType::Normal,
       });
     }
 
@@ -185,6 +196,15 @@ pub fn get_window_info(handle: i64) -> Result<Option<WindowInfo>> {
   Ok(windows.into_iter().find(|w| w.handle == handle))
 }
 
+/// Get the window title
+pub fn get_window_title(handle: i64) -> Result<String> {
+  Ok(
+    get_window_info(handle)?
+      .map(|w| w.title)
+      .unwrap_or_default(),
+  )
+}
+
 /// Set window always on top
 pub fn set_always_on_top(handle: i64, on_top: bool) -> Result<()> {
   unsafe {
@@ -212,3 +232,21 @@ pub fn set_window_opacity(handle: i64, opacity: f64) -> Result<()> {
   }
   Ok(())
 }
+
+/// Get window opacity
+pub fn get_window_opacity(handle: i64) -> Result<f64> {
+  unsafe {
+    let window = handle as id;
+    if window == nil {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let opacity: f64 = msg_send![window, alphaValue];
+    Ok(opacity)
+  }
+}
+
+/// Restore the window to full opacity
+pub fn remove_window_opacity(handle: i64) -> Result<()> {
+  set_window_opacity(handle, 1.0)
+}
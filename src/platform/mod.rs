@@ -9,23 +9,37 @@ mod windows;
 // Windows exports
 #[cfg(target_os = "windows")]
 pub use windows::{
-  close_window, focus_window, get_active_window, get_window_info, get_window_process_path,
-  get_windows, is_click_through, kill_window_process, set_always_on_top, set_click_through,
-  set_window_opacity, set_window_state, toggle_click_through, WindowState,
+  capture_window, close_window, focus_window, get_active_window, get_cursor_position, get_monitors,
+  get_pressed_modifiers, get_restored_bounds, get_window_bounds, get_window_flags,
+  get_window_icons, get_window_info, get_window_monitor, get_window_opacity,
+  get_window_process_path, get_window_state, get_window_title, get_windows, is_click_through,
+  kill_window_process, lower_window, place_window_above, raise_window, remove_window_opacity,
+  run_hotkey_loop, set_always_on_top, set_click_through, set_decorations, set_input_region,
+  set_overlay_visible, set_window_bounds, set_window_flags, set_window_icon, set_window_opacity,
+  set_window_state, set_window_title, set_window_type, toggle_click_through, wake_hotkey_loop,
+  watch_window_changes, watch_window_frames, watch_windows, WindowState,
 };
 
 // macOS exports
 #[cfg(target_os = "macos")]
 pub use macos::{
-  get_window_info, get_windows, is_click_through, set_always_on_top, set_click_through,
-  set_window_opacity, toggle_click_through,
+  get_window_info, get_window_opacity, get_window_title, get_windows, is_click_through,
+  remove_window_opacity, set_always_on_top, set_click_through, set_window_opacity,
+  toggle_click_through,
 };
 
 // Linux exports
 #[cfg(target_os = "linux")]
 pub use linux::{
-  get_window_info, get_windows, is_click_through, set_always_on_top, set_click_through,
-  set_window_opacity, toggle_click_through,
+  capture_window, close_window, focus_window, get_active_window, get_current_desktop,
+  get_cursor_position, get_desktops, get_monitors, get_pressed_modifiers, get_window_bounds,
+  get_window_flags, get_window_icons, get_window_info, get_window_monitor, get_window_opacity,
+  get_window_process_path, get_window_state, get_window_title, get_windows, is_click_through,
+  kill_window_process, lower_window, move_window_to_desktop, place_window_above, raise_window,
+  remove_window_opacity, run_hotkey_loop, set_always_on_top, set_click_through, set_decorations,
+  set_input_region, set_overlay_visible, set_window_bounds, set_window_flags, set_window_icon,
+  set_window_opacity, set_window_state, set_window_title, set_window_type, switch_desktop,
+  toggle_click_through, wake_hotkey_loop, watch_window_changes, watch_window_frames, watch_windows,
 };
 
 // Fallback for other platforms
@@ -71,9 +85,148 @@ pub enum WindowState {
   Minimize,
   Maximize,
   Restore,
+  Fullscreen,
+  ExitFullscreen,
+  Hide,
+  Show,
 }
 
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_monitors() -> napi::Result<Vec<crate::MonitorInfo>> {
+  Ok(vec![])
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_window_monitor(_handle: i64) -> napi::Result<Option<crate::MonitorInfo>> {
+  Ok(None)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_window_bounds(_handle: i64, _client_area: bool) -> napi::Result<Option<crate::Rect>> {
+  Ok(None)
+}
+
+/// Only Windows moves minimized windows off-screen, elsewhere the current frame is used
 #[cfg(not(target_os = "windows"))]
+pub fn get_restored_bounds(handle: i64) -> napi::Result<Option<crate::Rect>> {
+  get_window_bounds(handle, false)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_window_bounds(
+  _handle: i64,
+  _bounds: crate::Rect,
+  _client_area: bool,
+  _animate: bool,
+) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn get_window_title(_handle: i64) -> napi::Result<String> {
+  Ok(String::new())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_window_title(_handle: i64, _title: &str) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn raise_window(_handle: i64) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn lower_window(_handle: i64) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn place_window_above(_handle: i64, _sibling: i64) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_window_flags(_handle: i64) -> napi::Result<crate::WindowFlags> {
+  Ok(crate::WindowFlags::default())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_window_flags(_handle: i64, _flags: &crate::WindowFlags) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_window_type(_handle: i64, _window_type: crate::WindowType) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_input_region(_handle: i64, _rects: Option<&[crate::Rect]>) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_decorations(_handle: i64, _enabled: bool) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+// Virtual desktops are only exposed through EWMH on Linux
+#[cfg(not(target_os = "linux"))]
+pub fn get_desktops() -> napi::Result<Vec<crate::DesktopInfo>> {
+  Ok(vec![])
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_current_desktop() -> napi::Result<Option<u32>> {
+  Ok(None)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn switch_desktop(_index: u32) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn move_window_to_desktop(_handle: i64, _index: u32) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn get_window_process_path(_handle: i64) -> napi::Result<String> {
   Err(napi::Error::new(
     napi::Status::GenericFailure,
@@ -81,7 +234,7 @@ pub fn get_window_process_path(_handle: i64) -> napi::Result<String> {
   ))
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn close_window(_handle: i64) -> napi::Result<()> {
   Err(napi::Error::new(
     napi::Status::GenericFailure,
@@ -89,7 +242,7 @@ pub fn close_window(_handle: i64) -> napi::Result<()> {
   ))
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn focus_window(_handle: i64) -> napi::Result<()> {
   Err(napi::Error::new(
     napi::Status::GenericFailure,
@@ -97,12 +250,17 @@ pub fn focus_window(_handle: i64) -> napi::Result<()> {
   ))
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn get_active_window() -> napi::Result<Option<i64>> {
   Ok(None)
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_window_state(_handle: i64) -> napi::Result<Option<crate::WindowStateInfo>> {
+  Ok(None)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn set_window_state(_handle: i64, _state: WindowState) -> napi::Result<()> {
   Err(napi::Error::new(
     napi::Status::GenericFailure,
@@ -110,10 +268,125 @@ pub fn set_window_state(_handle: i64, _state: WindowState) -> napi::Result<()> {
   ))
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn kill_window_process(_handle: i64) -> napi::Result<()> {
   Err(napi::Error::new(
     napi::Status::GenericFailure,
     "Not implemented for this platform",
   ))
 }
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn wake_hotkey_loop() {}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_pressed_modifiers() -> napi::Result<u32> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn run_hotkey_loop(commands: std::sync::mpsc::Receiver<crate::hotkey::HotkeyCommand>) {
+  for command in commands {
+    if let crate::hotkey::HotkeyCommand::Register { reply, .. } = command {
+      let _ = reply.send(Err(napi::Error::new(
+        napi::Status::GenericFailure,
+        "Not implemented for this platform",
+      )));
+    }
+  }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_cursor_position() -> napi::Result<crate::CursorPosition> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn capture_window(
+  _handle: i64,
+  _region: Option<crate::Rect>,
+  _include_frame: bool,
+) -> napi::Result<crate::RgbaImage> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn watch_window_frames(
+  _handle: i64,
+  _region: Option<crate::Rect>,
+  _include_frame: bool,
+  _min_interval: std::time::Duration,
+  _stop: &std::sync::atomic::AtomicBool,
+  ready: std::sync::mpsc::Sender<napi::Result<()>>,
+  _on_frame: impl FnMut(crate::RgbaImage) -> bool,
+) {
+  let _ = ready.send(Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  )));
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_window_icons(_handle: i64) -> napi::Result<Vec<crate::RgbaImage>> {
+  Ok(vec![])
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_window_icon(_handle: i64, _images: &[crate::RgbaImage]) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn get_window_opacity(_handle: i64) -> napi::Result<f64> {
+  Ok(1.0)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn remove_window_opacity(_handle: i64) -> napi::Result<()> {
+  Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_overlay_visible(_handle: i64, _visible: bool) -> napi::Result<()> {
+  Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn watch_window_changes(
+  _handle: i64,
+  _stop: &std::sync::atomic::AtomicBool,
+  ready: std::sync::mpsc::Sender<napi::Result<()>>,
+  _on_change: impl FnMut(),
+) {
+  let _ = ready.send(Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  )));
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn watch_windows(
+  _stop: &std::sync::atomic::AtomicBool,
+  ready: std::sync::mpsc::Sender<napi::Result<()>>,
+  _on_event: impl FnMut(crate::rules::WindowEvent),
+) {
+  let _ = ready.send(Err(napi::Error::new(
+    napi::Status::GenericFailure,
+    "Not implemented for this platform",
+  )));
+}
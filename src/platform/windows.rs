@@ -1,22 +1,83 @@
-use crate::WindowInfo;
+use crate::hotkey::{self, HotkeyCommand};
+use crate::rules::WindowEvent;
+use crate::utils;
+use crate::{
+  CursorPosition, MonitorInfo, Rect, RgbaImage, WindowFlags, WindowInfo, WindowStateInfo,
+  WindowType,
+};
 use napi::bindgen_prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use windows::Win32::Foundation::{BOOL, HWND, LPARAM, MAX_PATH, RECT, TRUE, WPARAM};
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{
+  BOOL, HANDLE, HMODULE, HWND, LPARAM, MAX_PATH, POINT, RECT, TRUE, WPARAM,
+};
+use windows::Win32::Graphics::Dwm::{
+  DwmGetWindowAttribute, DwmSetWindowAttribute, DWMWA_TRANSITIONS_FORCEDISABLED,
+};
+use windows::Win32::Graphics::Gdi::{
+  BitBlt, ClientToScreen, CreateBitmap, CreateCompatibleBitmap, CreateCompatibleDC,
+  CreateDIBSection, DeleteDC, DeleteObject, EnumDisplayMonitors, EnumDisplaySettingsW, GetDC,
+  GetDIBits, GetMonitorInfoW, GetObjectW, GetWindowDC, MonitorFromRect, MonitorFromWindow,
+  ReleaseDC, SelectObject, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DEVMODEW, DIB_RGB_COLORS,
+  ENUM_CURRENT_SETTINGS, HBITMAP, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+  MONITOR_DEFAULTTONEAREST, SRCCOPY,
+};
+use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS, PW_CLIENTONLY};
 use windows::Win32::System::ProcessStatus::K32GetModuleFileNameExW;
 use windows::Win32::System::Threading::{
-  OpenProcess, TerminateProcess, PROCESS_QUERY_INFORMATION, PROCESS_TERMINATE, PROCESS_VM_READ,
+  GetCurrentThreadId, OpenProcess, TerminateProcess, INFINITE, PROCESS_QUERY_INFORMATION,
+  PROCESS_TERMINATE, PROCESS_VM_READ,
+};
+use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+  GetAsyncKeyState, RegisterHotKey, UnregisterHotKey, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT,
+  MOD_SHIFT, MOD_WIN, VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE,
+  VK_F1, VK_HOME, VK_INSERT, VK_LBUTTON, VK_LEFT, VK_LWIN, VK_MBUTTON, VK_MENU, VK_NEXT,
+  VK_OEM_PLUS, VK_PRIOR, VK_RBUTTON, VK_RETURN, VK_RIGHT, VK_RWIN, VK_SHIFT, VK_SNAPSHOT, VK_SPACE,
+  VK_TAB, VK_UP,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-  EnumWindows, GetClassNameW, GetForegroundWindow, GetWindowLongPtrW, GetWindowRect,
-  GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible, PostMessageW,
-  SetForegroundWindow, SetWindowLongPtrW, SetWindowPos, ShowWindow, GWL_EXSTYLE, HWND_NOTOPMOST,
-  HWND_TOPMOST, SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER,
-  SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, WINDOW_EX_STYLE, WM_CLOSE, WS_EX_LAYERED, WS_EX_NOACTIVATE,
-  WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT,
+  CreateIconIndirect, DestroyIcon, EnumWindows, FlashWindowEx, GetAncestor, GetClassLongPtrW,
+  GetClassNameW, GetClientRect, GetCursorPos, GetForegroundWindow, GetIconInfo,
+  GetLayeredWindowAttributes, GetSystemMetrics, GetWindow, GetWindowLongPtrW, GetWindowPlacement,
+  GetWindowRect, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic,
+  IsWindow, IsWindowVisible, IsZoomed, MsgWaitForMultipleObjects, PeekMessageW, PostMessageW,
+  PostThreadMessageW, SendMessageTimeoutW, SetForegroundWindow, SetWindowLongPtrW, SetWindowPos,
+  SetWindowTextW, ShowWindow, CHILDID_SELF, EVENT_OBJECT_DESTROY, EVENT_OBJECT_LOCATIONCHANGE,
+  EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_SHOW, EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MINIMIZEEND,
+  FLASHWINFO, FLASHW_STOP, FLASHW_TIMERNOFG, FLASHW_TRAY, GA_ROOT, GCLP_HICON, GCLP_HICONSM,
+  GWL_EXSTYLE, GWL_STYLE, GW_HWNDNEXT, GW_HWNDPREV, HICON, HWND_BOTTOM, HWND_NOTOPMOST, HWND_TOP,
+  HWND_TOPMOST, ICONINFO, ICON_BIG, ICON_SMALL, ICON_SMALL2, LAYERED_WINDOW_ATTRIBUTES_FLAGS,
+  LWA_ALPHA, MONITORINFOF_PRIMARY, MSG, OBJID_WINDOW, PM_NOREMOVE, PM_REMOVE, QS_ALLINPUT,
+  SMTO_ABORTIFHUNG, SM_CXICON, SM_CXSMICON, SM_SWAPBUTTON, SWP_FRAMECHANGED, SWP_NOACTIVATE,
+  SWP_NOMOVE, SWP_NOOWNERZORDER, SWP_NOSIZE, SWP_NOZORDER, SW_HIDE, SW_MAXIMIZE, SW_MINIMIZE,
+  SW_RESTORE, SW_SHOW, SW_SHOWNA, WINDOWPLACEMENT, WINDOW_EX_STYLE, WINEVENT_OUTOFCONTEXT,
+  WINEVENT_SKIPOWNPROCESS, WM_CLOSE, WM_GETICON, WM_HOTKEY, WM_NULL, WM_SETICON, WS_CAPTION,
+  WS_EX_APPWINDOW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST,
+  WS_EX_TRANSPARENT, WS_THICKFRAME,
 };
 
+lazy_static::lazy_static! {
+    /// Style and frame saved when a window enters borderless fullscreen
+    static ref FULLSCREEN_RESTORE: Mutex<HashMap<i64, (isize, RECT)>> = Mutex::new(HashMap::new());
+    /// Original style of windows whose decorations were removed
+    static ref DECORATION_RESTORE: Mutex<HashMap<i64, isize>> = Mutex::new(HashMap::new());
+    /// Tool window and no-activate bits windows had before `set_window_type` changed them
+    static ref WINDOW_TYPE_RESTORE: Mutex<HashMap<i64, isize>> = Mutex::new(HashMap::new());
+    /// Big and small icons created by `set_window_icon`, destroyed once replaced
+    static ref WINDOW_ICONS: Mutex<HashMap<i64, (isize, isize)>> = Mutex::new(HashMap::new());
+}
+
 /// Enable or disable click-through on a window
 pub fn set_click_through(handle: i64, enable: bool) -> Result<()> {
   unsafe {
@@ -55,6 +116,19 @@ pub fn set_click_through(handle: i64, enable: bool) -> Result<()> {
   Ok(())
 }
 
+/// Restrict mouse input to a set of rectangles
+/// Windows only offers whole-window click-through, so partial regions are rejected
+pub fn set_input_region(handle: i64, rects: Option<&[crate::Rect]>) -> Result<()> {
+  match rects {
+    None => set_click_through(handle, false),
+    Some([]) => set_click_through(handle, true),
+    Some(_) => Err(Error::new(
+      Status::GenericFailure,
+      "Partial input regions are not supported on Windows",
+    )),
+  }
+}
+
 /// Toggle click-through state. Returns new state.
 pub fn toggle_click_through(handle: i64) -> Result<bool> {
   let current = is_click_through(handle)?;
@@ -78,10 +152,12 @@ pub fn is_click_through(handle: i64) -> Result<bool> {
 /// Callback data for window enumeration
 struct EnumWindowsData {
   windows: Vec<WindowInfo>,
+  /// Number of top-level windows visited so far (EnumWindows goes top to bottom)
+  visited: i32,
 }
 
 /// Get window title
-fn get_window_title(hwnd: HWND) -> String {
+fn read_window_title(hwnd: HWND) -> String {
   unsafe {
     let len = GetWindowTextLengthW(hwnd);
     if len == 0 {
@@ -132,9 +208,69 @@ fn get_window_rect_info(hwnd: HWND) -> (i32, i32, i32, i32) {
   }
 }
 
+/// Count the top-level windows stacked below a window
+fn get_z_index(hwnd: HWND) -> i32 {
+  unsafe {
+    let mut below = 0;
+    let mut next = GetWindow(hwnd, GW_HWNDNEXT);
+    while next.0 != 0 {
+      below += 1;
+      next = GetWindow(next, GW_HWNDNEXT);
+    }
+    below
+  }
+}
+
+/// Read the window state from its show state and extended style
+fn read_window_state(hwnd: HWND) -> WindowStateInfo {
+  unsafe {
+    let ex_style = WINDOW_EX_STYLE(GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32);
+    let minimized = IsIconic(hwnd).as_bool();
+    let maximized = IsZoomed(hwnd).as_bool();
+
+    // Win32 has no fullscreen flag: an unmaximized window covering its whole monitor counts
+    let fullscreen = !minimized
+      && !maximized
+      && match (
+        get_frame_rect(hwnd),
+        get_monitor_info(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST)),
+      ) {
+        (Some(frame), Some(monitor)) => {
+          frame.x == monitor.bounds.x
+            && frame.y == monitor.bounds.y
+            && frame.width == monitor.bounds.width
+            && frame.height == monitor.bounds.height
+        }
+        _ => false,
+      };
+
+    WindowStateInfo {
+      minimized,
+      maximized,
+      fullscreen,
+      focused: GetForegroundWindow() == hwnd,
+      always_on_top: ex_style.contains(WS_EX_TOPMOST),
+    }
+  }
+}
+
+/// Derive a window type from the extended style (Win32 has no type hints)
+fn read_window_type(hwnd: HWND) -> WindowType {
+  unsafe {
+    let ex_style = WINDOW_EX_STYLE(GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32);
+    if ex_style.contains(WS_EX_TOOLWINDOW) {
+      WindowType::Utility
+    } else {
+      WindowType::Normal
+    }
+  }
+}
+
 /// Callback for EnumWindows
 unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
   let data = &mut *(lparam.0 as *mut EnumWindowsData);
+  let order = data.visited;
+  data.visited += 1;
 
   // Skip invisible windows
   if !IsWindowVisible(hwnd).as_bool() {
@@ -142,7 +278,7 @@ unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> B
   }
 
   // Get window title
-  let title = get_window_title(hwnd);
+  let title = read_window_title(hwnd);
 
   // Skip windows with empty titles (usually not user-facing windows)
   if title.is_empty() {
@@ -178,6 +314,8 @@ unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> B
 
   // Get window position and size
   let (x, y, width, height) = get_window_rect_info(hwnd);
+  let state = read_window_state(hwnd);
+  let window_type = read_window_type(hwnd);
 
   data.windows.push(WindowInfo {
     handle: hwnd.0 as i64,
@@ -190,6 +328,15 @@ unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> B
     width,
     height,
     path: get_window_process_path(hwnd.0 as i64).unwrap_or_default(),
+    // Converted to a bottom-up index once all windows have been visited
+    z_index: order,
+    desktop: -1,
+    minimized: state.minimized,
+    maximized: state.maximized,
+    fullscreen: state.fullscreen,
+    focused: state.focused,
+    always_on_top: state.always_on_top,
+    window_type,
   });
 
   TRUE
@@ -200,6 +347,7 @@ pub fn get_windows() -> Result<Vec<WindowInfo>> {
   unsafe {
     let mut data = EnumWindowsData {
       windows: Vec::new(),
+      visited: 0,
     };
 
     let _ = EnumWindows(
@@ -207,6 +355,10 @@ pub fn get_windows() -> Result<Vec<WindowInfo>> {
       LPARAM(&mut data as *mut _ as isize),
     );
 
+    for window in data.windows.iter_mut() {
+      window.z_index = data.visited - 1 - window.z_index;
+    }
+
     Ok(data.windows)
   }
 }
@@ -220,13 +372,15 @@ pub fn get_window_info(handle: i64) -> Result<Option<WindowInfo>> {
     }
 
     let visible = IsWindowVisible(hwnd).as_bool();
-    let title = get_window_title(hwnd);
+    let title = read_window_title(hwnd);
     let class_name = get_window_class(hwnd);
     let (x, y, width, height) = get_window_rect_info(hwnd);
 
     let mut process_id: u32 = 0;
     GetWindowThreadProcessId(hwnd, Some(&mut process_id));
 
+    let state = read_window_state(hwnd);
+
     Ok(Some(WindowInfo {
       handle,
       title,
@@ -238,6 +392,14 @@ pub fn get_window_info(handle: i64) -> Result<Option<WindowInfo>> {
       width,
       height,
       path: get_window_process_path(handle).unwrap_or_default(),
+      z_index: get_z_index(hwnd),
+      desktop: -1,
+      minimized: state.minimized,
+      maximized: state.maximized,
+      fullscreen: state.fullscreen,
+      focused: state.focused,
+      always_on_top: state.always_on_top,
+      window_type: read_window_type(hwnd),
     }))
   }
 }
@@ -276,7 +438,6 @@ pub fn set_window_opacity(handle: i64, opacity: f64) -> Result<()> {
   unsafe {
     use windows::Win32::Foundation::COLORREF;
     use windows::Win32::UI::WindowsAndMessaging::SetLayeredWindowAttributes;
-    use windows::Win32::UI::WindowsAndMessaging::LAYERED_WINDOW_ATTRIBUTES_FLAGS;
 
     let hwnd = HWND(handle as isize);
     if hwnd.0 == 0 {
@@ -309,6 +470,77 @@ pub fn set_window_opacity(handle: i64, opacity: f64) -> Result<()> {
   }
   Ok(())
 }
+
+/// Get window opacity from the layered window attributes (1.0 when not set)
+pub fn get_window_opacity(handle: i64) -> Result<f64> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as isize;
+    if (ex_style & WS_EX_LAYERED.0 as isize) == 0 {
+      return Ok(1.0);
+    }
+
+    let mut alpha: u8 = 255;
+    let mut flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS(0);
+    // Windows drawn with UpdateLayeredWindow have no attributes to read
+    if GetLayeredWindowAttributes(hwnd, None, Some(&mut alpha), Some(&mut flags)).is_err()
+      || flags.0 & LWA_ALPHA.0 == 0
+    {
+      return Ok(1.0);
+    }
+
+    Ok(alpha as f64 / 255.0)
+  }
+}
+
+/// Restore full opacity, dropping WS_EX_LAYERED unless click-through or a color key still needs it
+pub fn remove_window_opacity(handle: i64) -> Result<()> {
+  unsafe {
+    use windows::Win32::Foundation::COLORREF;
+    use windows::Win32::UI::WindowsAndMessaging::{SetLayeredWindowAttributes, LWA_COLORKEY};
+
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as isize;
+    if (ex_style & WS_EX_LAYERED.0 as isize) == 0 {
+      return Ok(());
+    }
+
+    // Windows without an alpha attribute are layered for another reason and left alone
+    let mut key = COLORREF(0);
+    let mut flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS(0);
+    if GetLayeredWindowAttributes(hwnd, Some(&mut key), None, Some(&mut flags)).is_err()
+      || flags.0 & LWA_ALPHA.0 == 0
+    {
+      return Ok(());
+    }
+
+    // Click-through and color keys still need the layered style, only the alpha is reset
+    if (ex_style & WS_EX_TRANSPARENT.0 as isize) != 0 || flags.0 & LWA_COLORKEY.0 != 0 {
+      return SetLayeredWindowAttributes(hwnd, key, 255, flags).map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("SetLayeredWindowAttributes failed: {}", e),
+        )
+      });
+    }
+
+    let new_style = ex_style & !(WS_EX_LAYERED.0 as isize);
+    #[cfg(target_pointer_width = "64")]
+    SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style);
+    #[cfg(target_pointer_width = "32")]
+    SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style as i32);
+  }
+  Ok(())
+}
+
 /// Get the executable path of the process that owns the window
 pub fn get_window_process_path(handle: i64) -> Result<String> {
   unsafe {
@@ -401,6 +633,22 @@ pub enum WindowState {
   Minimize,
   Maximize,
   Restore,
+  Fullscreen,
+  ExitFullscreen,
+  Hide,
+  Show,
+}
+
+/// Get the current window state
+pub fn get_window_state(handle: i64) -> Result<Option<WindowStateInfo>> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 || !IsWindow(hwnd).as_bool() {
+      return Ok(None);
+    }
+
+    Ok(Some(read_window_state(hwnd)))
+  }
 }
 
 /// Set the window state (Minimize, Maximize, Restore)
@@ -414,7 +662,15 @@ pub fn set_window_state(handle: i64, state: WindowState) -> Result<()> {
     let show_cmd = match state {
       WindowState::Minimize => SW_MINIMIZE,
       WindowState::Maximize => SW_MAXIMIZE,
-      WindowState::Restore => SW_RESTORE,
+      WindowState::Restore => {
+        // Restoring also leaves fullscreen, so the saved frame is not applied later
+        exit_fullscreen(hwnd)?;
+        SW_RESTORE
+      }
+      WindowState::Fullscreen => return enter_fullscreen(hwnd),
+      WindowState::ExitFullscreen => return exit_fullscreen(hwnd),
+      WindowState::Hide => SW_HIDE,
+      WindowState::Show => SW_SHOW,
     };
 
     ShowWindow(hwnd, show_cmd);
@@ -422,6 +678,97 @@ pub fn set_window_state(handle: i64, state: WindowState) -> Result<()> {
   Ok(())
 }
 
+/// Make a window borderless and cover its monitor, remembering the style and frame to restore
+fn enter_fullscreen(hwnd: HWND) -> Result<()> {
+  unsafe {
+    let mut saved = FULLSCREEN_RESTORE
+      .lock()
+      .map_err(|_| Error::new(Status::GenericFailure, "Fullscreen state is poisoned"))?;
+    if saved.contains_key(&(hwnd.0 as i64)) {
+      return Ok(());
+    }
+
+    let monitor = get_monitor_info(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST))
+      .ok_or_else(|| Error::new(Status::GenericFailure, "Cannot find the window's monitor"))?;
+
+    let mut frame = RECT::default();
+    GetWindowRect(hwnd, &mut frame).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("GetWindowRect failed: {}", e),
+      )
+    })?;
+
+    let style = GetWindowLongPtrW(hwnd, GWL_STYLE) as isize;
+    saved.insert(hwnd.0 as i64, (style, frame));
+
+    let new_style = style & !(WS_CAPTION.0 as isize | WS_THICKFRAME.0 as isize);
+
+    #[cfg(target_pointer_width = "64")]
+    SetWindowLongPtrW(hwnd, GWL_STYLE, new_style);
+    #[cfg(target_pointer_width = "32")]
+    SetWindowLongPtrW(hwnd, GWL_STYLE, new_style as i32);
+
+    SetWindowPos(
+      hwnd,
+      HWND_TOP,
+      monitor.bounds.x,
+      monitor.bounds.y,
+      monitor.bounds.width,
+      monitor.bounds.height,
+      SWP_FRAMECHANGED | SWP_NOOWNERZORDER,
+    )
+    .map_err(|e| {
+      // Put the frame back and forget the window, it never became fullscreen
+      #[cfg(target_pointer_width = "64")]
+      SetWindowLongPtrW(hwnd, GWL_STYLE, style);
+      #[cfg(target_pointer_width = "32")]
+      SetWindowLongPtrW(hwnd, GWL_STYLE, style as i32);
+      saved.remove(&(hwnd.0 as i64));
+
+      Error::new(
+        Status::GenericFailure,
+        format!("SetWindowPos failed: {}", e),
+      )
+    })
+  }
+}
+
+/// Restore the style and frame saved by `enter_fullscreen`
+fn exit_fullscreen(hwnd: HWND) -> Result<()> {
+  unsafe {
+    let saved = FULLSCREEN_RESTORE
+      .lock()
+      .map_err(|_| Error::new(Status::GenericFailure, "Fullscreen state is poisoned"))?
+      .remove(&(hwnd.0 as i64));
+
+    let Some((style, frame)) = saved else {
+      return Ok(());
+    };
+
+    #[cfg(target_pointer_width = "64")]
+    SetWindowLongPtrW(hwnd, GWL_STYLE, style);
+    #[cfg(target_pointer_width = "32")]
+    SetWindowLongPtrW(hwnd, GWL_STYLE, style as i32);
+
+    SetWindowPos(
+      hwnd,
+      HWND::default(),
+      frame.left,
+      frame.top,
+      frame.right - frame.left,
+      frame.bottom - frame.top,
+      SWP_FRAMECHANGED | SWP_NOZORDER | SWP_NOOWNERZORDER,
+    )
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("SetWindowPos failed: {}", e),
+      )
+    })
+  }
+}
+
 /// Kill the process associated with the window
 pub fn kill_window_process(handle: i64) -> Result<()> {
   unsafe {
@@ -449,3 +796,1344 @@ pub fn kill_window_process(handle: i64) -> Result<()> {
   }
   Ok(())
 }
+
+/// Build monitor info from a monitor handle
+fn get_monitor_info(hmonitor: HMONITOR) -> Option<MonitorInfo> {
+  unsafe {
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+
+    if !GetMonitorInfoW(
+      hmonitor,
+      &mut info as *mut MONITORINFOEXW as *mut MONITORINFO,
+    )
+    .as_bool()
+    {
+      return None;
+    }
+
+    let name_len = info
+      .szDevice
+      .iter()
+      .position(|&c| c == 0)
+      .unwrap_or(info.szDevice.len());
+    let name = OsString::from_wide(&info.szDevice[..name_len])
+      .to_string_lossy()
+      .into_owned();
+
+    let mut devmode = DEVMODEW {
+      dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+      ..Default::default()
+    };
+    let refresh_rate = if EnumDisplaySettingsW(
+      PCWSTR(info.szDevice.as_ptr()),
+      ENUM_CURRENT_SETTINGS,
+      &mut devmode,
+    )
+    .as_bool()
+    {
+      devmode.dmDisplayFrequency as f64
+    } else {
+      0.0
+    };
+
+    let mut dpi_x: u32 = 0;
+    let mut dpi_y: u32 = 0;
+    let scale_factor =
+      if GetDpiForMonitor(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y).is_ok() && dpi_x > 0
+      {
+        dpi_x as f64 / 96.0
+      } else {
+        1.0
+      };
+
+    let to_rect = |r: RECT| Rect {
+      x: r.left,
+      y: r.top,
+      width: r.right - r.left,
+      height: r.bottom - r.top,
+    };
+
+    Some(MonitorInfo {
+      name,
+      bounds: to_rect(info.monitorInfo.rcMonitor),
+      work_area: to_rect(info.monitorInfo.rcWork),
+      primary: (info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY) != 0,
+      refresh_rate,
+      scale_factor,
+    })
+  }
+}
+
+/// Callback for EnumDisplayMonitors
+unsafe extern "system" fn enum_monitors_callback(
+  hmonitor: HMONITOR,
+  _hdc: HDC,
+  _rect: *mut RECT,
+  lparam: LPARAM,
+) -> BOOL {
+  let monitors = &mut *(lparam.0 as *mut Vec<MonitorInfo>);
+  if let Some(info) = get_monitor_info(hmonitor) {
+    monitors.push(info);
+  }
+  TRUE
+}
+
+/// Get all connected monitors
+pub fn get_monitors() -> Result<Vec<MonitorInfo>> {
+  unsafe {
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+
+    let _ = EnumDisplayMonitors(
+      HDC::default(),
+      None,
+      Some(enum_monitors_callback),
+      LPARAM(&mut monitors as *mut _ as isize),
+    );
+
+    Ok(monitors)
+  }
+}
+
+/// Get the monitor that contains the largest part of the window
+pub fn get_window_monitor(handle: i64) -> Result<Option<MonitorInfo>> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    if hmonitor.is_invalid() {
+      return Ok(None);
+    }
+
+    Ok(get_monitor_info(hmonitor))
+  }
+}
+
+/// Get the client area of a window in screen coordinates
+fn get_client_rect(hwnd: HWND) -> Option<Rect> {
+  unsafe {
+    let mut rect = RECT::default();
+    GetClientRect(hwnd, &mut rect).ok()?;
+
+    let mut origin = POINT::default();
+    if !ClientToScreen(hwnd, &mut origin).as_bool() {
+      return None;
+    }
+
+    Some(Rect {
+      x: origin.x,
+      y: origin.y,
+      width: rect.right - rect.left,
+      height: rect.bottom - rect.top,
+    })
+  }
+}
+
+/// Get the outer frame of a window in screen coordinates
+fn get_frame_rect(hwnd: HWND) -> Option<Rect> {
+  unsafe {
+    let mut rect = RECT::default();
+    GetWindowRect(hwnd, &mut rect).ok()?;
+
+    Some(Rect {
+      x: rect.left,
+      y: rect.top,
+      width: rect.right - rect.left,
+      height: rect.bottom - rect.top,
+    })
+  }
+}
+
+/// Get window bounds, either the client area or the outer frame including decorations
+pub fn get_window_bounds(handle: i64, client_area: bool) -> Result<Option<Rect>> {
+  let hwnd = HWND(handle as isize);
+  if hwnd.0 == 0 {
+    return Ok(None);
+  }
+
+  if client_area {
+    Ok(get_client_rect(hwnd))
+  } else {
+    Ok(get_frame_rect(hwnd))
+  }
+}
+
+/// Get the outer frame a window returns to when restored, also while minimized or maximized
+pub fn get_restored_bounds(handle: i64) -> Result<Option<Rect>> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Ok(None);
+    }
+    if !IsIconic(hwnd).as_bool() && !IsZoomed(hwnd).as_bool() {
+      return Ok(get_frame_rect(hwnd));
+    }
+
+    let mut placement = WINDOWPLACEMENT {
+      length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+      ..Default::default()
+    };
+    if GetWindowPlacement(hwnd, &mut placement).is_err() {
+      return Ok(get_frame_rect(hwnd));
+    }
+    let normal = placement.rcNormalPosition;
+
+    // The normal position is relative to the work area, except for tool windows
+    let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as isize;
+    let (dx, dy) = if (ex_style & WS_EX_TOOLWINDOW.0 as isize) == 0 {
+      get_monitor_info(MonitorFromRect(&normal, MONITOR_DEFAULTTONEAREST))
+        .map(|m| (m.work_area.x - m.bounds.x, m.work_area.y - m.bounds.y))
+        .unwrap_or((0, 0))
+    } else {
+      (0, 0)
+    };
+
+    Ok(Some(Rect {
+      x: normal.left + dx,
+      y: normal.top + dy,
+      width: normal.right - normal.left,
+      height: normal.bottom - normal.top,
+    }))
+  }
+}
+
+/// Move and resize a window in a single SetWindowPos call
+pub fn set_window_bounds(
+  handle: i64,
+  bounds: Rect,
+  client_area: bool,
+  animate: bool,
+) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    // Translate client bounds using the current border sizes
+    let target = if client_area {
+      match (get_frame_rect(hwnd), get_client_rect(hwnd)) {
+        (Some(frame), Some(client)) => Rect {
+          x: bounds.x - (client.x - frame.x),
+          y: bounds.y - (client.y - frame.y),
+          width: bounds.width + (frame.width - client.width),
+          height: bounds.height + (frame.height - client.height),
+        },
+        _ => bounds,
+      }
+    } else {
+      bounds
+    };
+
+    // Transitions are disabled for the move only, restoring whatever value the window had.
+    // Left alone when the current value cannot be read back.
+    let mut previous = BOOL::default();
+    let suppress = !animate
+      && DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_TRANSITIONS_FORCEDISABLED,
+        &mut previous as *mut BOOL as *mut _,
+        std::mem::size_of::<BOOL>() as u32,
+      )
+      .is_ok();
+    let set_transitions_disabled = |disabled: BOOL| {
+      let _ = DwmSetWindowAttribute(
+        hwnd,
+        DWMWA_TRANSITIONS_FORCEDISABLED,
+        &disabled as *const BOOL as *const _,
+        std::mem::size_of::<BOOL>() as u32,
+      );
+    };
+
+    if suppress {
+      set_transitions_disabled(TRUE);
+    }
+
+    let result = SetWindowPos(
+      hwnd,
+      HWND::default(),
+      target.x,
+      target.y,
+      target.width,
+      target.height,
+      SWP_NOZORDER | SWP_NOACTIVATE,
+    );
+
+    if suppress {
+      set_transitions_disabled(previous);
+    }
+
+    result.map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("SetWindowPos failed: {}", e),
+      )
+    })?;
+  }
+  Ok(())
+}
+
+/// Get the window title
+pub fn get_window_title(handle: i64) -> Result<String> {
+  let hwnd = HWND(handle as isize);
+  if hwnd.0 == 0 {
+    return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+  }
+
+  Ok(read_window_title(hwnd))
+}
+
+/// Set the window title
+pub fn set_window_title(handle: i64, title: &str) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let wide: Vec<u16> = title.encode_utf16().chain(std::iter::once(0)).collect();
+
+    SetWindowTextW(hwnd, PCWSTR(wide.as_ptr())).map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("SetWindowTextW failed: {}", e),
+      )
+    })?;
+  }
+  Ok(())
+}
+
+/// Change the z-order of a window without moving or activating it
+fn set_z_order(hwnd: HWND, insert_after: HWND) -> Result<()> {
+  unsafe {
+    SetWindowPos(
+      hwnd,
+      insert_after,
+      0,
+      0,
+      0,
+      0,
+      SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+    )
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("SetWindowPos failed: {}", e),
+      )
+    })
+  }
+}
+
+/// Raise a window to the top of the stacking order
+pub fn raise_window(handle: i64) -> Result<()> {
+  let hwnd = HWND(handle as isize);
+  if hwnd.0 == 0 {
+    return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+  }
+
+  set_z_order(hwnd, HWND_TOP)
+}
+
+/// Lower a window to the bottom of the stacking order
+pub fn lower_window(handle: i64) -> Result<()> {
+  let hwnd = HWND(handle as isize);
+  if hwnd.0 == 0 {
+    return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+  }
+
+  set_z_order(hwnd, HWND_BOTTOM)
+}
+
+/// Restack a window directly above a sibling window
+pub fn place_window_above(handle: i64, sibling: i64) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    let sibling = HWND(sibling as isize);
+    if hwnd.0 == 0 || sibling.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    // SetWindowPos inserts *below* the given window, so target the one above the sibling
+    let above_sibling = GetWindow(sibling, GW_HWNDPREV);
+    if above_sibling == hwnd {
+      return Ok(());
+    }
+
+    if above_sibling.0 == 0 {
+      set_z_order(hwnd, HWND_TOP)
+    } else {
+      set_z_order(hwnd, above_sibling)
+    }
+  }
+}
+
+/// Get the window flags that have a Win32 equivalent
+pub fn get_window_flags(handle: i64) -> Result<WindowFlags> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let ex_style = WINDOW_EX_STYLE(GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32);
+
+    Ok(WindowFlags {
+      sticky: Some(false),
+      skip_taskbar: Some(
+        ex_style.contains(WS_EX_TOOLWINDOW) && !ex_style.contains(WS_EX_APPWINDOW),
+      ),
+      skip_pager: Some(false),
+      fullscreen: Some(false),
+      below: Some(false),
+      above: Some(ex_style.contains(WS_EX_TOPMOST)),
+      demands_attention: Some(false),
+    })
+  }
+}
+
+/// Apply the window flags that have a Win32 equivalent (sticky, skipPager and fullscreen are ignored)
+pub fn set_window_flags(handle: i64, flags: &WindowFlags) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    if let Some(skip) = flags.skip_taskbar {
+      let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as isize;
+      let new_style = if skip {
+        (ex_style | WS_EX_TOOLWINDOW.0 as isize) & !(WS_EX_APPWINDOW.0 as isize)
+      } else {
+        (ex_style & !(WS_EX_TOOLWINDOW.0 as isize)) | WS_EX_APPWINDOW.0 as isize
+      };
+
+      // The taskbar only notices the style change when the window is shown again
+      let visible = IsWindowVisible(hwnd).as_bool();
+      if visible {
+        ShowWindow(hwnd, SW_HIDE);
+      }
+
+      #[cfg(target_pointer_width = "64")]
+      SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style);
+      #[cfg(target_pointer_width = "32")]
+      SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style as i32);
+
+      if visible {
+        ShowWindow(hwnd, SW_SHOWNA);
+      }
+    }
+
+    if let Some(above) = flags.above {
+      set_always_on_top(handle, above)?;
+    }
+
+    if flags.below == Some(true) {
+      if flags.above != Some(true) {
+        set_always_on_top(handle, false)?;
+      }
+      set_z_order(hwnd, HWND_BOTTOM)?;
+    }
+
+    if let Some(attention) = flags.demands_attention {
+      let info = FLASHWINFO {
+        cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+        hwnd,
+        dwFlags: if attention {
+          FLASHW_TRAY | FLASHW_TIMERNOFG
+        } else {
+          FLASHW_STOP
+        },
+        uCount: 0,
+        dwTimeout: 0,
+      };
+      FlashWindowEx(&info);
+    }
+  }
+  Ok(())
+}
+
+/// Approximate a window type hint with extended styles: non-normal types become
+/// tool windows, and docks, tooltips and notifications also stop taking focus
+pub fn set_window_type(handle: i64, window_type: WindowType) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let tool = (WS_EX_TOOLWINDOW.0 | WS_EX_NOACTIVATE.0) as isize;
+    let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as isize;
+
+    let mut saved = WINDOW_TYPE_RESTORE
+      .lock()
+      .map_err(|_| Error::new(Status::GenericFailure, "Window type state is poisoned"))?;
+    // Bits the window had on its own are kept, only the ones added here are cleared again
+    let original = *saved.entry(handle).or_insert(style & tool);
+    let ex_style = style & !tool;
+
+    let new_style = match window_type {
+      WindowType::Normal | WindowType::Dialog => {
+        saved.remove(&handle);
+        ex_style | original
+      }
+      WindowType::Dock | WindowType::Tooltip | WindowType::Notification => ex_style | tool,
+      _ => ex_style | WS_EX_TOOLWINDOW.0 as isize | (original & WS_EX_NOACTIVATE.0 as isize),
+    };
+
+    #[cfg(target_pointer_width = "64")]
+    SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style);
+    #[cfg(target_pointer_width = "32")]
+    SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style as i32);
+
+    let _ = SetWindowPos(
+      hwnd,
+      HWND::default(),
+      0,
+      0,
+      0,
+      0,
+      SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED | SWP_NOACTIVATE,
+    );
+  }
+  Ok(())
+}
+
+/// Remove or restore the caption and sizing border of a window
+pub fn set_decorations(handle: i64, enabled: bool) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let frame_bits = WS_CAPTION.0 as isize | WS_THICKFRAME.0 as isize;
+    let style = GetWindowLongPtrW(hwnd, GWL_STYLE) as isize;
+
+    let mut saved = DECORATION_RESTORE
+      .lock()
+      .map_err(|_| Error::new(Status::GenericFailure, "Decoration state is poisoned"))?;
+
+    let new_style = if enabled {
+      match saved.remove(&handle) {
+        Some(original) => (style & !frame_bits) | (original & frame_bits),
+        None => style | frame_bits,
+      }
+    } else {
+      saved.entry(handle).or_insert(style);
+      style & !frame_bits
+    };
+
+    #[cfg(target_pointer_width = "64")]
+    SetWindowLongPtrW(hwnd, GWL_STYLE, new_style);
+    #[cfg(target_pointer_width = "32")]
+    SetWindowLongPtrW(hwnd, GWL_STYLE, new_style as i32);
+
+    // Refresh window to apply style changes
+    let _ = SetWindowPos(
+      hwnd,
+      HWND::default(),
+      0,
+      0,
+      0,
+      0,
+      SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED | SWP_NOACTIVATE,
+    );
+  }
+  Ok(())
+}
+
+/// Get the modifier keys currently held, as `utils::MOD_*` bits
+pub fn get_pressed_modifiers() -> Result<u32> {
+  let is_down = |key: VIRTUAL_KEY| unsafe { GetAsyncKeyState(key.0 as i32) as u16 & 0x8000 != 0 };
+
+  let mut modifiers = 0;
+  if is_down(VK_SHIFT) {
+    modifiers |= utils::MOD_SHIFT;
+  }
+  if is_down(VK_CONTROL) {
+    modifiers |= utils::MOD_CONTROL;
+  }
+  if is_down(VK_MENU) {
+    modifiers |= utils::MOD_ALT;
+  }
+  if is_down(VK_LWIN) || is_down(VK_RWIN) {
+    modifiers |= utils::MOD_SUPER;
+  }
+  Ok(modifiers)
+}
+
+/// Get the pointer position and button/modifier state (monitor is filled by the caller)
+pub fn get_cursor_position() -> Result<CursorPosition> {
+  let is_down = |key: VIRTUAL_KEY| unsafe { GetAsyncKeyState(key.0 as i32) as u16 & 0x8000 != 0 };
+
+  let mut point = POINT::default();
+  unsafe { GetCursorPos(&mut point) }.map_err(|e| {
+    Error::new(
+      Status::GenericFailure,
+      format!("GetCursorPos failed: {}", e),
+    )
+  })?;
+
+  // Button state follows the physical buttons, so honour swapped mouse buttons
+  let swapped = unsafe { GetSystemMetrics(SM_SWAPBUTTON) } != 0;
+  let (left, right) = if swapped {
+    (VK_RBUTTON, VK_LBUTTON)
+  } else {
+    (VK_LBUTTON, VK_RBUTTON)
+  };
+
+  Ok(CursorPosition {
+    x: point.x,
+    y: point.y,
+    monitor: None,
+    left_button: is_down(left),
+    middle_button: is_down(VK_MBUTTON),
+    right_button: is_down(right),
+    modifiers: utils::modifier_names(get_pressed_modifiers()?),
+  })
+}
+
+/// Capture the pixels of a window (or of a region of it) as RGBA
+pub fn capture_window(handle: i64, region: Option<Rect>, include_frame: bool) -> Result<RgbaImage> {
+  // Lets PrintWindow capture DirectComposition content (Windows 8.1+)
+  const PW_RENDERFULLCONTENT: PRINT_WINDOW_FLAGS = PRINT_WINDOW_FLAGS(2);
+
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let mut rect = RECT::default();
+    if include_frame {
+      GetWindowRect(hwnd, &mut rect)
+    } else {
+      GetClientRect(hwnd, &mut rect)
+    }
+    .map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to get window rect: {}", e),
+      )
+    })?;
+
+    let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+    let full = Rect {
+      x: 0,
+      y: 0,
+      width,
+      height,
+    };
+    let area = match region {
+      Some(region) => region.intersection(&full),
+      None => (width > 0 && height > 0).then_some(full),
+    }
+    .ok_or_else(|| Error::new(Status::InvalidArg, "Region is outside the window"))?;
+
+    let screen_dc = GetDC(HWND::default());
+    let mem_dc = CreateCompatibleDC(screen_dc);
+    let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+    let previous = SelectObject(mem_dc, bitmap);
+
+    let flags = if include_frame {
+      PW_RENDERFULLCONTENT
+    } else {
+      PRINT_WINDOW_FLAGS(PW_CLIENTONLY.0 | PW_RENDERFULLCONTENT.0)
+    };
+    if !PrintWindow(hwnd, mem_dc, flags).as_bool() {
+      // Fall back to copying what is currently on screen
+      let window_dc = if include_frame {
+        GetWindowDC(hwnd)
+      } else {
+        GetDC(hwnd)
+      };
+      let _ = BitBlt(mem_dc, 0, 0, width, height, window_dc, 0, 0, SRCCOPY);
+      ReleaseDC(hwnd, window_dc);
+    }
+    SelectObject(mem_dc, previous);
+
+    let mut info = BITMAPINFO {
+      bmiHeader: BITMAPINFOHEADER {
+        biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+        biWidth: width,
+        // Negative height asks for top-down rows
+        biHeight: -height,
+        biPlanes: 1,
+        biBitCount: 32,
+        biCompression: BI_RGB.0,
+        ..Default::default()
+      },
+      ..Default::default()
+    };
+    let mut bgra = vec![0u8; width as usize * height as usize * 4];
+    let lines = GetDIBits(
+      mem_dc,
+      bitmap,
+      0,
+      height as u32,
+      Some(bgra.as_mut_ptr() as *mut _),
+      &mut info,
+      DIB_RGB_COLORS,
+    );
+
+    DeleteObject(bitmap);
+    DeleteDC(mem_dc);
+    ReleaseDC(HWND::default(), screen_dc);
+
+    if lines == 0 {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Failed to read the captured bitmap",
+      ));
+    }
+
+    let mut rgba = Vec::with_capacity(area.width as usize * area.height as usize * 4);
+    for y in area.y..area.y + area.height {
+      let row = (y * width) as usize * 4;
+      for x in area.x..area.x + area.width {
+        let i = row + x as usize * 4;
+        rgba.extend_from_slice(&[bgra[i + 2], bgra[i + 1], bgra[i], 255]);
+      }
+    }
+
+    Ok(RgbaImage {
+      width: area.width as u32,
+      height: area.height as u32,
+      data: rgba.into(),
+    })
+  }
+}
+
+/// Capture a window at most once per `min_interval`, only delivering frames that changed
+/// `ready` receives the setup result; `on_frame` returns false when the frame was dropped
+pub fn watch_window_frames(
+  handle: i64,
+  region: Option<Rect>,
+  include_frame: bool,
+  min_interval: Duration,
+  stop: &AtomicBool,
+  ready: Sender<Result<()>>,
+  mut on_frame: impl FnMut(RgbaImage) -> bool,
+) {
+  if unsafe { !IsWindow(HWND(handle as isize)).as_bool() } {
+    let _ = ready.send(Err(Error::new(Status::InvalidArg, "Window not found")));
+    return;
+  }
+  let _ = ready.send(Ok(()));
+
+  // Windows has no damage notifications for foreign windows, so compare with the last frame
+  let mut previous: Option<Vec<u8>> = None;
+
+  while !stop.load(Ordering::Relaxed) {
+    let started = Instant::now();
+
+    if unsafe { !IsWindow(HWND(handle as isize)).as_bool() } {
+      break;
+    }
+
+    if let Ok(frame) = capture_window(handle, region, include_frame) {
+      if previous.as_deref() != Some(&frame.data[..]) {
+        let pixels = frame.data.to_vec();
+        // A dropped frame is retried so the latest content always gets through
+        previous = on_frame(frame).then_some(pixels);
+      }
+    }
+
+    thread::sleep(min_interval.saturating_sub(started.elapsed()));
+  }
+}
+
+/// Show or hide an attached overlay without activating it
+pub fn set_overlay_visible(handle: i64, visible: bool) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    ShowWindow(hwnd, if visible { SW_SHOWNA } else { SW_HIDE });
+  }
+  Ok(())
+}
+
+/// Longest a watcher thread waits for messages before checking its stop flag, in milliseconds
+const STOP_CHECK_INTERVAL: u32 = 100;
+
+/// Window watched by `watch_window_changes` on the current thread
+struct WatchedWindow {
+  hwnd: HWND,
+  changed: bool,
+  destroyed: bool,
+}
+
+thread_local! {
+    static WATCHED_WINDOW: RefCell<Option<WatchedWindow>> = const { RefCell::new(None) };
+}
+
+unsafe extern "system" fn window_change_proc(
+  _hook: HWINEVENTHOOK,
+  event: u32,
+  hwnd: HWND,
+  id_object: i32,
+  _id_child: i32,
+  _event_thread: u32,
+  _event_time: u32,
+) {
+  WATCHED_WINDOW.with(|watched| {
+    let mut watched = watched.borrow_mut();
+    let Some(watched) = watched.as_mut() else {
+      return;
+    };
+
+    // Focus moving anywhere may hide or show the overlay
+    if event == EVENT_SYSTEM_FOREGROUND {
+      watched.changed = true;
+    } else if hwnd == watched.hwnd && id_object == OBJID_WINDOW.0 {
+      if event == EVENT_OBJECT_DESTROY {
+        watched.destroyed = true;
+      } else {
+        watched.changed = true;
+      }
+    }
+  });
+}
+
+/// Call `on_change` whenever the geometry, stacking, state or focus of a window may have changed
+/// Returns once `stop` is set or the window is destroyed; `ready` receives the setup result
+pub fn watch_window_changes(
+  handle: i64,
+  stop: &AtomicBool,
+  ready: Sender<Result<()>>,
+  mut on_change: impl FnMut(),
+) {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 || !IsWindow(hwnd).as_bool() {
+      let _ = ready.send(Err(Error::new(Status::InvalidArg, "Window not found")));
+      return;
+    }
+
+    WATCHED_WINDOW.with(|watched| {
+      *watched.borrow_mut() = Some(WatchedWindow {
+        hwnd,
+        changed: false,
+        destroyed: false,
+      })
+    });
+
+    // Object events are limited to the target's thread, focus and minimize events are global
+    let mut process_id = 0u32;
+    let thread_id = GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+    let hooks = [
+      SetWinEventHook(
+        EVENT_OBJECT_DESTROY,
+        EVENT_OBJECT_LOCATIONCHANGE,
+        HMODULE(0),
+        Some(window_change_proc),
+        process_id,
+        thread_id,
+        WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+      ),
+      SetWinEventHook(
+        EVENT_SYSTEM_FOREGROUND,
+        EVENT_SYSTEM_MINIMIZEEND,
+        HMODULE(0),
+        Some(window_change_proc),
+        0,
+        0,
+        WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+      ),
+    ];
+    if hooks.iter().any(|hook| hook.0 == 0) {
+      for hook in hooks.iter().filter(|hook| hook.0 != 0) {
+        UnhookWinEvent(*hook);
+      }
+      WATCHED_WINDOW.with(|watched| *watched.borrow_mut() = None);
+      let _ = ready.send(Err(Error::new(
+        Status::GenericFailure,
+        "SetWinEventHook failed",
+      )));
+      return;
+    }
+    let _ = ready.send(Ok(()));
+
+    on_change();
+
+    while !stop.load(Ordering::Relaxed) {
+      let mut msg = MSG::default();
+      while PeekMessageW(&mut msg, HWND(0), 0, 0, PM_REMOVE).as_bool() {}
+
+      let (changed, destroyed) = WATCHED_WINDOW.with(|watched| {
+        watched
+          .borrow_mut()
+          .as_mut()
+          .map(|w| (std::mem::take(&mut w.changed), w.destroyed))
+          .unwrap_or((false, true))
+      });
+      if destroyed || !IsWindow(hwnd).as_bool() {
+        break;
+      }
+      if changed {
+        on_change();
+      }
+
+      // Hook callbacks are delivered while pumping messages, so wake up as they arrive
+      MsgWaitForMultipleObjects(None, false, STOP_CHECK_INTERVAL, QS_ALLINPUT);
+    }
+
+    for hook in hooks {
+      UnhookWinEvent(hook);
+    }
+    WATCHED_WINDOW.with(|watched| *watched.borrow_mut() = None);
+  }
+}
+
+thread_local! {
+    /// Events collected by `window_event_proc` for `watch_windows` on the current thread
+    static WINDOW_EVENTS: RefCell<Vec<WindowEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+unsafe extern "system" fn window_event_proc(
+  _hook: HWINEVENTHOOK,
+  event: u32,
+  hwnd: HWND,
+  id_object: i32,
+  id_child: i32,
+  _event_thread: u32,
+  _event_time: u32,
+) {
+  if id_object != OBJID_WINDOW.0 || id_child != CHILDID_SELF as i32 || hwnd.0 == 0 {
+    return;
+  }
+
+  let event = if event == EVENT_OBJECT_DESTROY {
+    WindowEvent::Closed(hwnd.0 as i64)
+  } else if GetAncestor(hwnd, GA_ROOT) == hwnd {
+    WindowEvent::Changed(hwnd.0 as i64)
+  } else {
+    return;
+  };
+  WINDOW_EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+/// Report windows appearing, being renamed or destroyed until `stop` is set
+/// `ready` receives the setup result; events are only sent after it
+pub fn watch_windows(
+  stop: &AtomicBool,
+  ready: Sender<Result<()>>,
+  mut on_event: impl FnMut(WindowEvent),
+) {
+  unsafe {
+    let flags = WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS;
+    let hooks = [
+      SetWinEventHook(
+        EVENT_OBJECT_DESTROY,
+        EVENT_OBJECT_SHOW,
+        HMODULE(0),
+        Some(window_event_proc),
+        0,
+        0,
+        flags,
+      ),
+      SetWinEventHook(
+        EVENT_OBJECT_NAMECHANGE,
+        EVENT_OBJECT_NAMECHANGE,
+        HMODULE(0),
+        Some(window_event_proc),
+        0,
+        0,
+        flags,
+      ),
+    ];
+    if hooks.iter().any(|hook| hook.0 == 0) {
+      for hook in hooks.iter().filter(|hook| hook.0 != 0) {
+        UnhookWinEvent(*hook);
+      }
+      let _ = ready.send(Err(Error::new(
+        Status::GenericFailure,
+        "SetWinEventHook failed",
+      )));
+      return;
+    }
+    let _ = ready.send(Ok(()));
+
+    while !stop.load(Ordering::Relaxed) {
+      let mut msg = MSG::default();
+      while PeekMessageW(&mut msg, HWND(0), 0, 0, PM_REMOVE).as_bool() {}
+
+      let events = WINDOW_EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()));
+      events.into_iter().for_each(&mut on_event);
+
+      // Hook callbacks are delivered while pumping messages, so wake up as they arrive
+      MsgWaitForMultipleObjects(None, false, STOP_CHECK_INTERVAL, QS_ALLINPUT);
+    }
+
+    for hook in hooks {
+      UnhookWinEvent(hook);
+    }
+    WINDOW_EVENTS.with(|events| events.borrow_mut().clear());
+  }
+}
+
+/// Read a bitmap as top-down 32-bit BGRA rows
+unsafe fn read_bitmap_bgra(dc: HDC, bitmap: HBITMAP, width: i32, height: i32) -> Option<Vec<u8>> {
+  let mut info = BITMAPINFO {
+    bmiHeader: BITMAPINFOHEADER {
+      biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+      biWidth: width,
+      biHeight: -height,
+      biPlanes: 1,
+      biBitCount: 32,
+      biCompression: BI_RGB.0,
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let mut bgra = vec![0u8; width as usize * height as usize * 4];
+  let lines = GetDIBits(
+    dc,
+    bitmap,
+    0,
+    height as u32,
+    Some(bgra.as_mut_ptr() as *mut _),
+    &mut info,
+    DIB_RGB_COLORS,
+  );
+  (lines != 0).then_some(bgra)
+}
+
+/// Convert a color icon to RGBA, monochrome icons are skipped
+unsafe fn icon_to_rgba(icon: HICON) -> Option<RgbaImage> {
+  let mut info = ICONINFO::default();
+  GetIconInfo(icon, &mut info).ok()?;
+
+  let image = (|| {
+    if info.hbmColor.0 == 0 {
+      return None;
+    }
+
+    let mut bitmap = BITMAP::default();
+    if GetObjectW(
+      info.hbmColor,
+      std::mem::size_of::<BITMAP>() as i32,
+      Some(&mut bitmap as *mut BITMAP as *mut _),
+    ) == 0
+    {
+      return None;
+    }
+    let (width, height) = (bitmap.bmWidth, bitmap.bmHeight);
+
+    let dc = CreateCompatibleDC(HDC::default());
+    let color = read_bitmap_bgra(dc, info.hbmColor, width, height);
+    let mask = read_bitmap_bgra(dc, info.hbmMask, width, height);
+    DeleteDC(dc);
+    let color = color?;
+
+    // Icons without an alpha channel rely on the AND mask (white = transparent)
+    let has_alpha = color.chunks_exact(4).any(|p| p[3] != 0);
+    let mut rgba = Vec::with_capacity(color.len());
+    for (i, p) in color.chunks_exact(4).enumerate() {
+      let alpha = if has_alpha {
+        p[3]
+      } else {
+        match &mask {
+          Some(mask) if mask[i * 4] != 0 => 0,
+          _ => 255,
+        }
+      };
+      rgba.extend_from_slice(&[p[2], p[1], p[0], alpha]);
+    }
+
+    Some(RgbaImage {
+      width: width as u32,
+      height: height as u32,
+      data: rgba.into(),
+    })
+  })();
+
+  DeleteObject(info.hbmColor);
+  DeleteObject(info.hbmMask);
+  image
+}
+
+/// Read the icons of a window (WM_GETICON) and of its class, one per size
+pub fn get_window_icons(handle: i64) -> Result<Vec<RgbaImage>> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let mut handles: Vec<isize> = Vec::new();
+    for kind in [ICON_BIG, ICON_SMALL2, ICON_SMALL] {
+      let mut result: usize = 0;
+      // Hung windows must not block us
+      SendMessageTimeoutW(
+        hwnd,
+        WM_GETICON,
+        WPARAM(kind as usize),
+        LPARAM(0),
+        SMTO_ABORTIFHUNG,
+        100,
+        Some(&mut result),
+      );
+      handles.push(result as isize);
+    }
+    for index in [GCLP_HICON, GCLP_HICONSM] {
+      handles.push(GetClassLongPtrW(hwnd, index) as isize);
+    }
+
+    let mut icons: Vec<RgbaImage> = Vec::new();
+    let mut seen: Vec<isize> = Vec::new();
+    for icon in handles {
+      if icon == 0 || seen.contains(&icon) {
+        continue;
+      }
+      seen.push(icon);
+
+      if let Some(image) = icon_to_rgba(HICON(icon)) {
+        if !icons
+          .iter()
+          .any(|i| i.width == image.width && i.height == image.height)
+        {
+          icons.push(image);
+        }
+      }
+    }
+
+    Ok(icons)
+  }
+}
+
+/// Create an icon from an RGBA image, transparency coming from the alpha channel
+unsafe fn rgba_to_icon(image: &RgbaImage) -> Option<HICON> {
+  let (width, height) = (image.width as i32, image.height as i32);
+  let info = BITMAPINFO {
+    bmiHeader: BITMAPINFOHEADER {
+      biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+      biWidth: width,
+      biHeight: -height,
+      biPlanes: 1,
+      biBitCount: 32,
+      biCompression: BI_RGB.0,
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+
+  let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+  let color = CreateDIBSection(
+    HDC::default(),
+    &info,
+    DIB_RGB_COLORS,
+    &mut bits,
+    HANDLE::default(),
+    0,
+  )
+  .ok()?;
+
+  let bgra = std::slice::from_raw_parts_mut(bits as *mut u8, image.data.len());
+  for (dst, src) in bgra.chunks_exact_mut(4).zip(image.data.chunks_exact(4)) {
+    dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+  }
+
+  // An all-zero AND mask leaves transparency to the alpha channel
+  let mask = CreateBitmap(width, height, 1, 1, None);
+  let icon_info = ICONINFO {
+    fIcon: TRUE,
+    xHotspot: 0,
+    yHotspot: 0,
+    hbmMask: mask,
+    hbmColor: color,
+  };
+  let icon = CreateIconIndirect(&icon_info).ok();
+
+  DeleteObject(color);
+  DeleteObject(mask);
+  icon
+}
+
+/// Set the big and small icons of a window (WM_SETICON) from the best-fitting images
+pub fn set_window_icon(handle: i64, images: &[RgbaImage]) -> Result<()> {
+  unsafe {
+    let hwnd = HWND(handle as isize);
+    if hwnd.0 == 0 {
+      return Err(Error::new(Status::InvalidArg, "Invalid window handle"));
+    }
+
+    let closest = |target: i32| {
+      images
+        .iter()
+        .min_by_key(|image| (image.width.max(image.height) as i32 - target).abs())
+    };
+
+    let mut created = [0isize; 2];
+    for (slot, (kind, metric)) in [(ICON_BIG, SM_CXICON), (ICON_SMALL, SM_CXSMICON)]
+      .into_iter()
+      .enumerate()
+    {
+      let Some(icon) = closest(GetSystemMetrics(metric)).and_then(|image| rgba_to_icon(image))
+      else {
+        continue;
+      };
+      created[slot] = icon.0;
+
+      SendMessageTimeoutW(
+        hwnd,
+        WM_SETICON,
+        WPARAM(kind as usize),
+        LPARAM(icon.0),
+        SMTO_ABORTIFHUNG,
+        100,
+        None,
+      );
+    }
+
+    if created == [0, 0] {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Failed to create the window icon",
+      ));
+    }
+
+    // The window no longer references the icons we created before
+    if let Ok(mut icons) = WINDOW_ICONS.lock() {
+      if let Some(previous) = icons.insert(handle, (created[0], created[1])) {
+        for icon in [previous.0, previous.1] {
+          if icon != 0 {
+            let _ = DestroyIcon(HICON(icon));
+          }
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Map an accelerator key name to its virtual-key code
+fn virtual_key(key: &str) -> Option<u32> {
+  let lower = key.to_ascii_lowercase();
+  let vk = match lower.as_str() {
+    "esc" | "escape" => VK_ESCAPE,
+    "enter" | "return" => VK_RETURN,
+    "space" => VK_SPACE,
+    "tab" => VK_TAB,
+    "backspace" => VK_BACK,
+    "delete" | "del" => VK_DELETE,
+    "insert" | "ins" => VK_INSERT,
+    "home" => VK_HOME,
+    "end" => VK_END,
+    "pageup" => VK_PRIOR,
+    "pagedown" => VK_NEXT,
+    "up" => VK_UP,
+    "down" => VK_DOWN,
+    "left" => VK_LEFT,
+    "right" => VK_RIGHT,
+    "printscreen" | "print" => VK_SNAPSHOT,
+    "plus" => VK_OEM_PLUS,
+    _ if lower.len() > 1 && lower.starts_with('f') => {
+      let n: u32 = lower[1..].parse().ok()?;
+      return (1..=24).contains(&n).then(|| VK_F1.0 as u32 + n - 1);
+    }
+    _ => {
+      let mut chars = lower.chars();
+      return match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Some(c.to_ascii_uppercase() as u32),
+        _ => None,
+      };
+    }
+  };
+  Some(vk.0 as u32)
+}
+
+/// Id of the hotkey thread, 0 while it is not running
+static HOTKEY_THREAD: AtomicU32 = AtomicU32::new(0);
+
+/// Wake the hotkey thread so it handles the commands just sent
+pub fn wake_hotkey_loop() {
+  let thread_id = HOTKEY_THREAD.load(Ordering::SeqCst);
+  if thread_id != 0 {
+    unsafe {
+      let _ = PostThreadMessageW(thread_id, WM_NULL, WPARAM(0), LPARAM(0));
+    }
+  }
+}
+
+/// Own the hotkey registrations and deliver WM_HOTKEY until the command channel closes
+pub fn run_hotkey_loop(commands: Receiver<HotkeyCommand>) {
+  // Hotkeys are bound to the registering thread's queue, so all calls happen here.
+  // Peeking creates that queue, `wake_hotkey_loop` can post to it from then on
+  unsafe {
+    let mut msg = MSG::default();
+    let _ = PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_NOREMOVE);
+    HOTKEY_THREAD.store(GetCurrentThreadId(), Ordering::SeqCst);
+  }
+
+  loop {
+    unsafe {
+      let mut msg = MSG::default();
+      while PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+        if msg.message == WM_HOTKEY {
+          hotkey::dispatch(msg.wParam.0 as u32);
+        }
+      }
+    }
+
+    loop {
+      match commands.try_recv() {
+        Ok(HotkeyCommand::Register {
+          id,
+          modifiers,
+          key,
+          reply,
+        }) => {
+          let result = match virtual_key(&key) {
+            None => Err(Error::new(
+              Status::InvalidArg,
+              format!("Unknown key: {}", key),
+            )),
+            Some(vk) => {
+              let mut flags = MOD_NOREPEAT;
+              if modifiers & utils::MOD_SHIFT != 0 {
+                flags |= MOD_SHIFT;
+              }
+              if modifiers & utils::MOD_CONTROL != 0 {
+                flags |= MOD_CONTROL;
+              }
+              if modifiers & utils::MOD_ALT != 0 {
+                flags |= MOD_ALT;
+              }
+              if modifiers & utils::MOD_SUPER != 0 {
+                flags |= MOD_WIN;
+              }
+
+              unsafe { RegisterHotKey(HWND::default(), id as i32, flags, vk) }.map_err(|e| {
+                Error::new(
+                  Status::GenericFailure,
+                  format!("Hotkey is already registered by another application: {}", e),
+                )
+              })
+            }
+          };
+          let _ = reply.send(result);
+        }
+        Ok(HotkeyCommand::Unregister { id }) => unsafe {
+          let _ = UnregisterHotKey(HWND::default(), id as i32);
+        },
+        Err(TryRecvError::Empty) => break,
+        Err(TryRecvError::Disconnected) => {
+          let thread_id = unsafe { GetCurrentThreadId() };
+          let _ = HOTKEY_THREAD.compare_exchange(thread_id, 0, Ordering::SeqCst, Ordering::SeqCst);
+          return;
+        }
+      }
+    }
+
+    // Hotkeys and wake-ups both arrive as messages. Commands are drained after
+    // the queue, so a wake-up removed above never hides a pending command
+    unsafe { MsgWaitForMultipleObjects(None, false, INFINITE, QS_ALLINPUT) };
+  }
+}
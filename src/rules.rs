@@ -0,0 +1,217 @@
+use napi::bindgen_prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+
+use crate::{platform, utils, Rect, WindowInfo, WindowMatch, WindowRule, WindowRuleActions};
+
+/// Window lifecycle notifications sent by the platform watcher
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+pub enum WindowEvent {
+  /// A window appeared, was shown or renamed
+  Changed(i64),
+  /// A window was destroyed
+  Closed(i64),
+}
+
+#[derive(Default)]
+struct Engine {
+  next_id: u32,
+  rules: Vec<WindowRule>,
+  /// Rules each window matched last time, a rule applies again only once it stopped matching
+  matched: HashMap<i64, HashSet<u32>>,
+  /// Stop flag of the watcher thread, running while there are rules
+  watcher: Option<Arc<AtomicBool>>,
+}
+
+impl Engine {
+  /// Stop the watcher once the last rule is gone
+  fn stop_if_idle(&mut self) {
+    if self.rules.is_empty() {
+      if let Some(stop) = self.watcher.take() {
+        stop.store(true, Ordering::Relaxed);
+      }
+      self.matched.clear();
+    }
+  }
+}
+
+lazy_static::lazy_static! {
+    static ref ENGINE: Mutex<Engine> = Mutex::new(Engine::default());
+}
+
+fn lock_engine() -> Result<MutexGuard<'static, Engine>> {
+  ENGINE
+    .lock()
+    .map_err(|_| Error::new(Status::GenericFailure, "Window rule state is poisoned"))
+}
+
+/// Spawn the platform watcher feeding `handle_event`
+fn start_watcher() -> Result<Arc<AtomicBool>> {
+  let stop = Arc::new(AtomicBool::new(false));
+  let (ready, started) = mpsc::channel();
+
+  let thread_stop = stop.clone();
+  thread::spawn(move || {
+    platform::watch_windows(&thread_stop, ready, handle_event);
+
+    // Let the next rule start a new watcher if this one ended on its own
+    if let Ok(mut engine) = ENGINE.lock() {
+      if engine
+        .watcher
+        .as_ref()
+        .is_some_and(|s| Arc::ptr_eq(s, &thread_stop))
+      {
+        engine.watcher = None;
+      }
+    }
+  });
+
+  started
+    .recv()
+    .map_err(|_| Error::new(Status::GenericFailure, "Window rule thread exited"))??;
+  Ok(stop)
+}
+
+/// Register a rule, applied to windows that start matching it from now on
+pub fn add(criteria: WindowMatch, actions: WindowRuleActions, once: bool) -> Result<u32> {
+  let mut engine = lock_engine()?;
+  if engine.watcher.is_none() {
+    engine.watcher = Some(start_watcher()?);
+  }
+
+  engine.next_id += 1;
+  let id = engine.next_id;
+  engine.rules.push(WindowRule {
+    id,
+    window_match: criteria,
+    actions,
+    once,
+  });
+  Ok(id)
+}
+
+/// Remove a rule, returns false if no rule has this id
+pub fn remove(id: u32) -> Result<bool> {
+  let mut engine = lock_engine()?;
+  let count = engine.rules.len();
+  engine.rules.retain(|r| r.id != id);
+  if engine.rules.len() == count {
+    return Ok(false);
+  }
+
+  for matched in engine.matched.values_mut() {
+    matched.remove(&id);
+  }
+  engine.stop_if_idle();
+  Ok(true)
+}
+
+/// Registered rules, in the order they are evaluated
+pub fn list() -> Result<Vec<WindowRule>> {
+  Ok(lock_engine()?.rules.clone())
+}
+
+/// Called from the platform thread for every window event
+fn handle_event(event: WindowEvent) {
+  let handle = match event {
+    WindowEvent::Changed(handle) => handle,
+    WindowEvent::Closed(handle) => {
+      if let Ok(mut engine) = ENGINE.lock() {
+        engine.matched.remove(&handle);
+      }
+      return;
+    }
+  };
+
+  let Ok(Some(window)) = platform::get_window_info(handle) else {
+    return;
+  };
+
+  let due: Vec<WindowRuleActions> = {
+    let Ok(mut engine) = ENGINE.lock() else {
+      return;
+    };
+    let Engine { rules, matched, .. } = &mut *engine;
+
+    let applied = matched.entry(handle).or_default();
+    let mut due = Vec::new();
+    rules.retain(|rule| {
+      if !window.visible || !utils::window_matches(&rule.window_match, &window) {
+        applied.remove(&rule.id);
+        return true;
+      }
+      if applied.insert(rule.id) {
+        due.push(rule.actions.clone());
+        return !rule.once;
+      }
+      true
+    });
+
+    engine.stop_if_idle();
+    due
+  };
+
+  // Actions run without the lock, failures are ignored as there is no caller to report to
+  for actions in due {
+    apply(&window, &actions);
+  }
+}
+
+/// Bounds of `window` moved onto `monitor`, keeping its offset in the work area where it fits
+fn bounds_on_monitor(window: &WindowInfo, monitor: u32) -> Option<Rect> {
+  let monitors = platform::get_monitors().ok()?;
+  let target = monitors.get(monitor as usize)?.work_area;
+  let current = platform::get_window_bounds(window.handle, false).ok()??;
+  let origin = platform::get_window_monitor(window.handle)
+    .ok()
+    .flatten()
+    .map(|m| m.work_area)
+    .unwrap_or(target);
+
+  let width = current.width.min(target.width);
+  let height = current.height.min(target.height);
+  let x = (current.x - origin.x).clamp(0, target.width - width);
+  let y = (current.y - origin.y).clamp(0, target.height - height);
+
+  Some(Rect {
+    x: target.x + x,
+    y: target.y + y,
+    width,
+    height,
+  })
+}
+
+/// Run every action set on the rule, in a fixed order
+fn apply(window: &WindowInfo, actions: &WindowRuleActions) {
+  let handle = window.handle;
+
+  if let Some(bounds) = actions
+    .bounds
+    .or_else(|| actions.monitor.and_then(|m| bounds_on_monitor(window, m)))
+  {
+    let _ = platform::set_window_bounds(handle, bounds, false, false);
+  }
+  if let Some(desktop) = actions.desktop {
+    let _ = platform::move_window_to_desktop(handle, desktop);
+  }
+  if let Some(state) = actions.state {
+    let _ = platform::set_window_state(handle, state.into());
+  }
+  if let Some(flags) = &actions.flags {
+    let _ = platform::set_window_flags(handle, flags);
+  }
+  if let Some(decorations) = actions.decorations {
+    let _ = platform::set_decorations(handle, decorations);
+  }
+  if let Some(on_top) = actions.always_on_top {
+    let _ = platform::set_always_on_top(handle, on_top);
+  }
+  if let Some(opacity) = actions.opacity {
+    let _ = platform::set_window_opacity(handle, opacity.clamp(0.0, 1.0));
+  }
+  if let Some(click_through) = actions.click_through {
+    let _ = platform::set_click_through(handle, click_through);
+  }
+}
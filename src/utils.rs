@@ -1,6 +1,8 @@
 use napi::bindgen_prelude::*;
 use napi::{Error, Result, Status, ValueType};
 
+use crate::{Rect, WindowInfo, WindowMatch};
+
 pub fn to_i64(value: Unknown) -> Result<i64> {
   match value.get_type()? {
     ValueType::Number => value.coerce_to_number()?.get_int64(),
@@ -28,3 +30,357 @@ pub fn to_i64(value: Unknown) -> Result<i64> {
     }
   }
 }
+
+/// Modifier key bits shared by the platform layers
+pub const MOD_SHIFT: u32 = 1 << 0;
+pub const MOD_CONTROL: u32 = 1 << 1;
+pub const MOD_ALT: u32 = 1 << 2;
+pub const MOD_SUPER: u32 = 1 << 3;
+
+/// Map a modifier name (case-insensitive) to its bit
+pub fn parse_modifier(name: &str) -> Option<u32> {
+  match name.trim().to_ascii_lowercase().as_str() {
+    "shift" => Some(MOD_SHIFT),
+    "ctrl" | "control" => Some(MOD_CONTROL),
+    "alt" | "option" => Some(MOD_ALT),
+    "super" | "meta" | "win" | "cmd" | "command" => Some(MOD_SUPER),
+    _ => None,
+  }
+}
+
+/// Names of the modifiers set in a bit mask, in accelerator order
+pub fn modifier_names(mask: u32) -> Vec<String> {
+  [
+    (MOD_CONTROL, "Ctrl"),
+    (MOD_ALT, "Alt"),
+    (MOD_SHIFT, "Shift"),
+    (MOD_SUPER, "Super"),
+  ]
+  .into_iter()
+  .filter(|(bit, _)| mask & bit != 0)
+  .map(|(_, name)| name.to_string())
+  .collect()
+}
+
+/// Parse a `+` separated modifier list such as `Ctrl+Alt` into a bit mask
+pub fn parse_modifiers(modifiers: &str) -> Result<u32> {
+  let mut mask = 0;
+  for name in modifiers.split('+').filter(|n| !n.trim().is_empty()) {
+    mask |= parse_modifier(name).ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Unknown modifier key: {}", name.trim()),
+      )
+    })?;
+  }
+
+  if mask == 0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "At least one modifier key is required",
+    ));
+  }
+  Ok(mask)
+}
+
+/// Split an accelerator such as `Ctrl+Shift+F9` into its modifier mask and key name
+pub fn parse_accelerator(accelerator: &str) -> Result<(u32, String)> {
+  let parts: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+  let (key, modifiers) = parts
+    .split_last()
+    .filter(|(key, _)| !key.is_empty())
+    .ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Invalid accelerator: {}", accelerator),
+      )
+    })?;
+
+  let mut mask = 0;
+  for name in modifiers {
+    mask |= parse_modifier(name).ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Unknown modifier key in {}: {}", accelerator, name),
+      )
+    })?;
+  }
+  Ok((mask, key.to_string()))
+}
+
+/// Convert the opaque pixels of an RGBA image into run-length rectangles
+/// Runs repeating the run right above them are merged into a taller rectangle
+pub fn alpha_mask_to_rects(
+  rgba: &[u8],
+  width: u32,
+  height: u32,
+  threshold: u8,
+) -> Result<Vec<Rect>> {
+  let (width, height) = (width as usize, height as usize);
+  if rgba.len() < width * height * 4 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "Buffer holds {} bytes, {}x{} RGBA needs {}",
+        rgba.len(),
+        width,
+        height,
+        width * height * 4
+      ),
+    ));
+  }
+
+  let mut rects: Vec<Rect> = Vec::new();
+  // Indices into `rects` of the runs found on the previous row
+  let mut previous: Vec<usize> = Vec::new();
+
+  for y in 0..height {
+    let row = &rgba[y * width * 4..(y + 1) * width * 4];
+    let mut current = Vec::new();
+    let mut x = 0;
+
+    while x < width {
+      if row[x * 4 + 3] <= threshold {
+        x += 1;
+        continue;
+      }
+
+      let start = x;
+      while x < width && row[x * 4 + 3] > threshold {
+        x += 1;
+      }
+
+      let above = previous
+        .iter()
+        .copied()
+        .find(|&i| rects[i].x == start as i32 && rects[i].width == (x - start) as i32);
+
+      match above {
+        Some(i) => {
+          rects[i].height += 1;
+          current.push(i);
+        }
+        None => {
+          rects.push(Rect {
+            x: start as i32,
+            y: y as i32,
+            width: (x - start) as i32,
+            height: 1,
+          });
+          current.push(rects.len() - 1);
+        }
+      }
+    }
+
+    previous = current;
+  }
+
+  Ok(rects)
+}
+
+/// Whether an executable path matches a full path, or only a file name when no separator is given
+fn path_matches(path: &str, pattern: &str) -> bool {
+  if pattern.contains(['/', '\\']) {
+    path.eq_ignore_ascii_case(pattern)
+  } else {
+    path
+      .rsplit(['/', '\\'])
+      .next()
+      .is_some_and(|name| name.eq_ignore_ascii_case(pattern))
+  }
+}
+
+/// Whether a window meets every criterion set in `criteria`
+pub fn window_matches(criteria: &WindowMatch, window: &WindowInfo) -> bool {
+  criteria
+    .title
+    .as_ref()
+    .is_none_or(|title| window.title.to_lowercase().contains(&title.to_lowercase()))
+    && criteria
+      .class_name
+      .as_ref()
+      .is_none_or(|class_name| window.class_name.eq_ignore_ascii_case(class_name))
+    && criteria
+      .path
+      .as_ref()
+      .is_none_or(|path| path_matches(&window.path, path))
+    && criteria
+      .process_id
+      .is_none_or(|process_id| window.process_id == process_id)
+    && criteria
+      .window_type
+      .is_none_or(|window_type| window.window_type == window_type)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parts(r: &Rect) -> (i32, i32, i32, i32) {
+    (r.x, r.y, r.width, r.height)
+  }
+
+  fn window(title: &str, class_name: &str, path: &str) -> WindowInfo {
+    WindowInfo {
+      handle: 1,
+      title: title.to_string(),
+      process_id: 42,
+      class_name: class_name.to_string(),
+      visible: true,
+      x: 0,
+      y: 0,
+      width: 800,
+      height: 600,
+      path: path.to_string(),
+      z_index: 0,
+      desktop: 0,
+      minimized: false,
+      maximized: false,
+      fullscreen: false,
+      focused: false,
+      always_on_top: false,
+      window_type: crate::WindowType::Normal,
+    }
+  }
+
+  /// Build a `width` x `height` RGBA image from per-pixel alpha values, row by row
+  fn image(width: usize, alpha: &[u8]) -> Vec<u8> {
+    assert_eq!(alpha.len() % width, 0);
+    alpha.iter().flat_map(|&a| [255, 255, 255, a]).collect()
+  }
+
+  #[test]
+  fn parse_modifiers_accepts_aliases_in_any_case() {
+    assert_eq!(parse_modifiers("ctrl+ALT").unwrap(), MOD_CONTROL | MOD_ALT);
+    assert_eq!(
+      parse_modifiers(" Control + Shift + Cmd ").unwrap(),
+      MOD_CONTROL | MOD_SHIFT | MOD_SUPER
+    );
+    assert_eq!(parse_modifiers("option+meta").unwrap(), MOD_ALT | MOD_SUPER);
+  }
+
+  #[test]
+  fn parse_modifiers_rejects_unknown_or_missing_modifiers() {
+    assert!(parse_modifiers("Ctrl+Hyper").is_err());
+    assert!(parse_modifiers("").is_err());
+    assert!(parse_modifiers("+").is_err());
+  }
+
+  #[test]
+  fn parse_accelerator_splits_modifiers_and_key() {
+    assert_eq!(
+      parse_accelerator("Ctrl+Shift+F9").unwrap(),
+      (MOD_CONTROL | MOD_SHIFT, "F9".to_string())
+    );
+    assert_eq!(
+      parse_accelerator(" alt + space ").unwrap(),
+      (MOD_ALT, "space".to_string())
+    );
+    assert_eq!(parse_accelerator("F12").unwrap(), (0, "F12".to_string()));
+  }
+
+  #[test]
+  fn parse_accelerator_rejects_invalid_accelerators() {
+    assert!(parse_accelerator("").is_err());
+    assert!(parse_accelerator("Ctrl+").is_err());
+    assert!(parse_accelerator("Hyper+A").is_err());
+  }
+
+  #[test]
+  fn alpha_mask_splits_runs_on_threshold() {
+    let rgba = image(6, &[0, 200, 200, 128, 129, 255]);
+    let rects = alpha_mask_to_rects(&rgba, 6, 1, 128).unwrap();
+    let rects: Vec<_> = rects.iter().map(parts).collect();
+    assert_eq!(rects, vec![(1, 0, 2, 1), (4, 0, 2, 1)]);
+  }
+
+  #[test]
+  fn alpha_mask_merges_identical_runs_of_consecutive_rows() {
+    #[rustfmt::skip]
+    let rgba = image(4, &[
+      0, 255, 255, 0,
+      0, 255, 255, 0,
+      255, 255, 255, 255,
+      0, 255, 255, 0,
+    ]);
+    let rects = alpha_mask_to_rects(&rgba, 4, 4, 0).unwrap();
+    let rects: Vec<_> = rects.iter().map(parts).collect();
+    assert_eq!(rects, vec![(1, 0, 2, 2), (0, 2, 4, 1), (1, 3, 2, 1)]);
+  }
+
+  #[test]
+  fn alpha_mask_of_transparent_or_empty_images() {
+    let rgba = image(3, &[0, 10, 0, 0, 0, 10]);
+    assert!(alpha_mask_to_rects(&rgba, 3, 2, 10).unwrap().is_empty());
+    assert!(alpha_mask_to_rects(&[], 0, 0, 0).unwrap().is_empty());
+    assert!(alpha_mask_to_rects(&[], 0, 5, 0).unwrap().is_empty());
+    assert!(alpha_mask_to_rects(&[], 5, 0, 0).unwrap().is_empty());
+  }
+
+  #[test]
+  fn alpha_mask_rejects_short_buffers() {
+    let rgba = image(2, &[255, 255]);
+    assert!(alpha_mask_to_rects(&rgba, 2, 2, 0).is_err());
+  }
+
+  #[test]
+  fn path_matches_full_paths_or_file_names() {
+    assert!(path_matches("/usr/bin/firefox", "/usr/bin/firefox"));
+    assert!(path_matches(
+      r"C:\Windows\notepad.exe",
+      r"c:\windows\NOTEPAD.EXE"
+    ));
+    assert!(path_matches("/usr/bin/firefox", "Firefox"));
+    assert!(path_matches(r"C:\Windows\notepad.exe", "notepad.exe"));
+    assert!(!path_matches("/usr/bin/firefox", "/opt/firefox"));
+    assert!(!path_matches("/usr/bin/firefox", "fire"));
+    assert!(!path_matches("", "firefox"));
+  }
+
+  #[test]
+  fn window_matches_every_set_criterion() {
+    let w = window(
+      "Inbox - Mail",
+      "Thunderbird",
+      "/usr/lib/thunderbird/thunderbird",
+    );
+
+    assert!(window_matches(&WindowMatch::default(), &w));
+    assert!(window_matches(
+      &WindowMatch {
+        title: Some("inbox".into()),
+        class_name: Some("thunderbird".into()),
+        path: Some("thunderbird".into()),
+        process_id: Some(42),
+        window_type: Some(crate::WindowType::Normal),
+      },
+      &w
+    ));
+
+    let mismatches = [
+      WindowMatch {
+        title: Some("outbox".into()),
+        ..Default::default()
+      },
+      WindowMatch {
+        class_name: Some("thunder".into()),
+        ..Default::default()
+      },
+      WindowMatch {
+        path: Some("/usr/bin/thunderbird".into()),
+        ..Default::default()
+      },
+      WindowMatch {
+        process_id: Some(7),
+        ..Default::default()
+      },
+      WindowMatch {
+        window_type: Some(crate::WindowType::Dialog),
+        ..Default::default()
+      },
+    ];
+    for criteria in &mismatches {
+      assert!(!window_matches(criteria, &w));
+    }
+  }
+}